@@ -0,0 +1,173 @@
+use crate::url_helpers::remove_fragment_from_url;
+use json_trait_rs::{JsonMapTrait, JsonType};
+use std::{collections::HashMap, sync::Arc};
+use url::Url;
+
+// Keywords whose values are not schemas, so identifiers defined within them should not be indexed
+const NOT_SCHEMA_KEYWORDS: [&str; 4] = ["const", "default", "enum", "examples"];
+// Keywords whose values are maps of schemas, so their keys are not keywords
+const SCHEMA_MAP_KEYWORDS: [&str; 5] = ["$defs", "definitions", "dependentSchemas", "patternProperties", "properties"];
+
+/// Resource (document or embedded sub-document) identified while indexing a JSON document.
+#[derive(Debug, PartialEq)]
+pub(in crate) struct IndexedResource {
+    /// URLs identifying the resource (the first one is the URL used to resolve relative identifiers)
+    pub(in crate) urls: Vec<Url>,
+    /// Reference tokens locating the resource within the indexed document
+    pub(in crate) reference_tokens: Vec<String>,
+    /// Plain-name fragments mapped to the reference tokens of the identified value (relative to the resource)
+    pub(in crate) anchors: HashMap<String, Vec<String>>,
+}
+
+impl IndexedResource {
+    fn new(url: Url, reference_tokens: Vec<String>) -> Self {
+        Self {
+            urls: vec![url],
+            reference_tokens,
+            anchors: HashMap::new(),
+        }
+    }
+}
+
+/// Resources embedded into a document, valid as long as the document is the cached one.
+///
+/// Embedded resources are looked up only via the document embedding them, so a document could not shadow others by declaring their URLs.
+#[derive(Debug)]
+pub(in crate) struct DocumentIndex<T> {
+    document: Arc<T>,
+    resources: Vec<IndexedResource>,
+}
+
+impl<T: JsonType> DocumentIndex<T> {
+    pub(in crate) fn new(url: &Url, document: Arc<T>) -> Self {
+        let resources = index_document(url, &*document);
+        Self { document, resources }
+    }
+
+    pub(in crate) fn document(&self) -> &Arc<T> {
+        &self.document
+    }
+
+    /// Whether the index was built from `document` (and not from a previously cached version of it).
+    pub(in crate) fn is_index_of(&self, document: &Arc<T>) -> bool {
+        Arc::ptr_eq(&self.document, document)
+    }
+
+    /// Resource representing the whole document.
+    pub(in crate) fn root(&self) -> &IndexedResource {
+        &self.resources[0]
+    }
+
+    /// Resource identified by `url` (its fragment is ignored), if defined within the document.
+    pub(in crate) fn resource(&self, url: &Url) -> Option<&IndexedResource> {
+        let fragmentless_url = remove_fragment_from_url(url);
+        self.resources.iter().find(|resource| resource.urls.contains(&fragmentless_url))
+    }
+}
+
+/// Index the resources and the anchors (`$anchor`, `$dynamicAnchor` and `$id` in the `#name` form) defined by `document`.
+///
+/// The first returned resource always represents the whole document, retrieved from `url`.
+fn index_document<T: JsonType>(url: &Url, document: &T) -> Vec<IndexedResource> {
+    let mut resources = vec![IndexedResource::new(remove_fragment_from_url(url), Vec::new())];
+    index_value(document, None, &mut Vec::new(), 0, &mut resources);
+    resources
+}
+
+fn index_value<T: JsonType>(value: &T, parent_key: Option<&str>, reference_tokens: &mut Vec<String>, resource_index: usize, resources: &mut Vec<IndexedResource>) {
+    if let Some(object) = value.as_object() {
+        let mut resource_index = resource_index;
+
+        if let Some(id) = value.get_attribute("$id").and_then(JsonType::as_string) {
+            if let Some(anchor) = id.strip_prefix('#') {
+                add_anchor(anchor, reference_tokens, &mut resources[resource_index]);
+            } else if let Ok(resource_url) = resources[resource_index].urls[0].join(id).map(|url| remove_fragment_from_url(&url)) {
+                if reference_tokens.is_empty() {
+                    // The document declares its own identifier, so it could be referenced via it too
+                    if !resources[resource_index].urls.contains(&resource_url) {
+                        resources[resource_index].urls.insert(0, resource_url);
+                    }
+                } else {
+                    resources.push(IndexedResource::new(resource_url, reference_tokens.clone()));
+                    resource_index = resources.len() - 1;
+                }
+            }
+        }
+
+        for anchor_keyword in &["$anchor", "$dynamicAnchor"] {
+            if let Some(anchor) = value.get_attribute(anchor_keyword).and_then(JsonType::as_string) {
+                add_anchor(anchor, reference_tokens, &mut resources[resource_index]);
+            }
+        }
+
+        let keys_are_keywords = parent_key.map_or(true, |parent_key| !SCHEMA_MAP_KEYWORDS.contains(&parent_key));
+        for (key, child) in object.items() {
+            if keys_are_keywords && NOT_SCHEMA_KEYWORDS.contains(&key) {
+                continue;
+            }
+            reference_tokens.push(key.to_string());
+            index_value(child, Some(key), reference_tokens, resource_index, resources);
+            let _d = reference_tokens.pop();
+        }
+    } else if let Some(items) = value.as_array() {
+        for (index, item) in items.enumerate() {
+            reference_tokens.push(index.to_string());
+            index_value(item, None, reference_tokens, resource_index, resources);
+            let _d = reference_tokens.pop();
+        }
+    }
+}
+
+fn add_anchor(anchor: &str, reference_tokens: &[String], resource: &mut IndexedResource) {
+    if !anchor.is_empty() {
+        let relative_reference_tokens = &reference_tokens[resource.reference_tokens.len()..];
+        let _d = resource.anchors.entry(anchor.to_string()).or_insert_with(|| relative_reference_tokens.to_vec());
+    }
+}
+
+#[cfg(all(test, feature = "trait_serde_json"))]
+mod tests {
+    use super::{index_document, IndexedResource};
+    use url::Url;
+
+    #[test]
+    fn test_index_document() {
+        let document = json!({
+            "$id": "https://example.com/root.json",
+            "$defs": {
+                "anchored": {"$anchor": "foo"},
+                "legacy": {"$id": "#bar"},
+                "embedded": {
+                    "$id": "embedded.json",
+                    "$defs": {"inner": {"$anchor": "foo"}},
+                },
+                "enum": {"$dynamicAnchor": "property-named-as-a-keyword"},
+            },
+            "examples": [{"$anchor": "not-a-schema"}],
+            "items": [{"$anchor": "in-array"}],
+        });
+
+        assert_eq!(
+            index_document(&Url::parse("memory:///root.json#/ignored").unwrap(), &document),
+            vec![
+                IndexedResource {
+                    urls: vec![Url::parse("https://example.com/root.json").unwrap(), Url::parse("memory:///root.json").unwrap()],
+                    reference_tokens: vec![],
+                    anchors: vec![
+                        ("foo".to_string(), vec!["$defs".to_string(), "anchored".to_string()]),
+                        ("bar".to_string(), vec!["$defs".to_string(), "legacy".to_string()]),
+                        ("property-named-as-a-keyword".to_string(), vec!["$defs".to_string(), "enum".to_string()]),
+                        ("in-array".to_string(), vec!["items".to_string(), "0".to_string()]),
+                    ]
+                    .into_iter()
+                    .collect(),
+                },
+                IndexedResource {
+                    urls: vec![Url::parse("https://example.com/embedded.json").unwrap()],
+                    reference_tokens: vec!["$defs".to_string(), "embedded".to_string()],
+                    anchors: vec![("foo".to_string(), vec!["$defs".to_string(), "inner".to_string()])].into_iter().collect(),
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,48 @@
+use crate::{json::resolve_reference_token, url_helpers::json_pointer_from_reference_tokens};
+use json_trait_rs::JsonType;
+use std::{ops::Deref, sync::Arc};
+
+/// Value identified by an URL fragment, alongside the document that contains it and its location within it.
+///
+/// The fragment shares the allocation of the document, so no part of the document is copied: dereferencing it walks
+/// the document along the reference tokens, which are resolved on creation.
+#[derive(Debug)]
+pub struct JsonFragment<T: JsonType> {
+    document: Arc<T>,
+    reference_tokens: Vec<String>,
+}
+
+impl<T: JsonType> JsonFragment<T> {
+    /// Crate-internal constructor, `reference_tokens` are expected to identify a value within `document`.
+    pub(in crate) fn new(document: Arc<T>, reference_tokens: Vec<String>) -> Self {
+        Self { document, reference_tokens }
+    }
+
+    /// Document containing the fragment.
+    #[must_use]
+    pub fn document(&self) -> &Arc<T> {
+        &self.document
+    }
+
+    /// JSON pointer locating the fragment within the document.
+    #[must_use]
+    pub fn json_pointer(&self) -> String {
+        json_pointer_from_reference_tokens(&self.reference_tokens)
+    }
+
+    /// Value identified by the fragment, borrowed from the document.
+    #[must_use]
+    pub fn value(&self) -> &T {
+        self
+    }
+}
+
+impl<T: JsonType> Deref for JsonFragment<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        self.reference_tokens.iter().fold(&*self.document, |value, reference_token| {
+            resolve_reference_token(value, reference_token).expect("Reference tokens are resolved on creation")
+        })
+    }
+}
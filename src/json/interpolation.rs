@@ -0,0 +1,150 @@
+use crate::loader::error::LoaderError;
+use json_trait_rs::RustType;
+
+/// Interpolator of environment variables into the string values of the loaded documents.
+///
+/// `${VAR}` is replaced with the value of `VAR`, `${VAR:-default}` falls back to `default` if `VAR`
+/// is not defined (or empty) and `$$` is replaced with a literal `$`.
+/// References to undefined variables are replaced with an empty string, unless the interpolator is strict,
+/// while unterminated references are always reported as errors.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct EnvInterpolator {
+    strict: bool,
+}
+
+impl EnvInterpolator {
+    /// Interpolator failing on references to undefined variables without default value.
+    #[must_use]
+    pub const fn strict() -> Self {
+        Self { strict: true }
+    }
+
+    pub fn interpolate(self, string: &str) -> Result<String, LoaderError> {
+        let mut interpolated_string = String::with_capacity(string.len());
+        let mut remaining_string = string;
+        while let Some(dollar_index) = remaining_string.find('$') {
+            interpolated_string.push_str(&remaining_string[..dollar_index]);
+            remaining_string = &remaining_string[dollar_index..];
+
+            if remaining_string.starts_with("$$") {
+                interpolated_string.push('$');
+                remaining_string = &remaining_string[2..];
+            } else if remaining_string.starts_with("${") {
+                let end_index = remaining_string
+                    .find('}')
+                    .ok_or_else(|| LoaderError::FormatError(format!("Unterminated variable reference in '{}'", string)))?;
+                interpolated_string.push_str(&self.resolve_reference(&remaining_string[2..end_index])?);
+                remaining_string = &remaining_string[end_index + 1..];
+            } else {
+                interpolated_string.push('$');
+                remaining_string = &remaining_string[1..];
+            }
+        }
+        interpolated_string.push_str(remaining_string);
+        Ok(interpolated_string)
+    }
+
+    /// Resolve the content of a `${...}` reference, which is either `VAR` or `VAR:-default`.
+    fn resolve_reference(self, reference: &str) -> Result<String, LoaderError> {
+        let (name, default_value) = match reference.find(":-") {
+            Some(separator_index) => (&reference[..separator_index], Some(&reference[separator_index + 2..])),
+            None => (reference, None),
+        };
+
+        match (std::env::var(name), default_value) {
+            (Ok(value), Some(default_value)) if value.is_empty() => Ok(default_value.to_string()),
+            (Ok(value), _) => Ok(value),
+            (Err(_), Some(default_value)) => Ok(default_value.to_string()),
+            (Err(_), None) if self.strict => Err(LoaderError::FormatError(format!("Environment variable '{}' is not defined", name))),
+            (Err(_), None) => Ok(String::new()),
+        }
+    }
+}
+
+/// JSON-like values whose string values could be interpolated.
+pub trait Interpolate {
+    fn interpolate_strings(&mut self, interpolator: EnvInterpolator) -> Result<(), LoaderError>;
+}
+
+impl Interpolate for RustType {
+    fn interpolate_strings(&mut self, interpolator: EnvInterpolator) -> Result<(), LoaderError> {
+        match self {
+            Self::String(string_value) => *string_value = interpolator.interpolate(string_value)?,
+            Self::List(items) => items.iter_mut().try_for_each(|item| item.interpolate_strings(interpolator))?,
+            Self::Object(object) => object.values_mut().try_for_each(|value| value.interpolate_strings(interpolator))?,
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "json")]
+impl Interpolate for json::JsonValue {
+    fn interpolate_strings(&mut self, interpolator: EnvInterpolator) -> Result<(), LoaderError> {
+        match self {
+            Self::Short(short_value) => *self = Self::String(interpolator.interpolate(short_value.as_str())?),
+            Self::String(string_value) => *string_value = interpolator.interpolate(string_value)?,
+            Self::Array(items) => items.iter_mut().try_for_each(|item| item.interpolate_strings(interpolator))?,
+            Self::Object(object) => object.iter_mut().try_for_each(|(_, value)| value.interpolate_strings(interpolator))?,
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "serde_json")]
+impl Interpolate for serde_json::Value {
+    fn interpolate_strings(&mut self, interpolator: EnvInterpolator) -> Result<(), LoaderError> {
+        match self {
+            Self::String(string_value) => *string_value = interpolator.interpolate(string_value)?,
+            Self::Array(items) => items.iter_mut().try_for_each(|item| item.interpolate_strings(interpolator))?,
+            Self::Object(object) => object.values_mut().try_for_each(|value| value.interpolate_strings(interpolator))?,
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "serde_yaml")]
+impl Interpolate for serde_yaml::Value {
+    fn interpolate_strings(&mut self, interpolator: EnvInterpolator) -> Result<(), LoaderError> {
+        match self {
+            Self::String(string_value) => *string_value = interpolator.interpolate(string_value)?,
+            Self::Sequence(items) => items.iter_mut().try_for_each(|item| item.interpolate_strings(interpolator))?,
+            Self::Mapping(mapping) => mapping.values_mut().try_for_each(|value| value.interpolate_strings(interpolator))?,
+            Self::Tagged(tagged_value) => tagged_value.value.interpolate_strings(interpolator)?,
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EnvInterpolator;
+    use crate::loader::error::LoaderError;
+    use test_case::test_case;
+
+    #[test_case("no references" => "no references" ; "no_references")]
+    #[test_case("${LOADER_RS_INTERPOLATION_VAR}" => "value" ; "reference")]
+    #[test_case("a ${LOADER_RS_INTERPOLATION_VAR} b ${LOADER_RS_INTERPOLATION_VAR}" => "a value b value" ; "multiple_references")]
+    #[test_case("${LOADER_RS_INTERPOLATION_VAR:-default}" => "value" ; "defined_with_default")]
+    #[test_case("${LOADER_RS_INTERPOLATION_EMPTY:-default}" => "default" ; "empty_with_default")]
+    #[test_case("${LOADER_RS_INTERPOLATION_UNDEFINED:-}" => "" ; "undefined_with_empty_default")]
+    #[test_case("${LOADER_RS_INTERPOLATION_UNDEFINED}" => "" ; "undefined")]
+    #[test_case("$$ $${LOADER_RS_INTERPOLATION_VAR} $5" => "$ ${LOADER_RS_INTERPOLATION_VAR} $5" ; "escaped")]
+    fn test_interpolate(string: &str) -> String {
+        std::env::set_var("LOADER_RS_INTERPOLATION_VAR", "value");
+        std::env::set_var("LOADER_RS_INTERPOLATION_EMPTY", "");
+        EnvInterpolator::default().interpolate(string).unwrap()
+    }
+
+    #[test_case("${LOADER_RS_INTERPOLATION_UNDEFINED}", "Environment variable 'LOADER_RS_INTERPOLATION_UNDEFINED' is not defined")]
+    #[test_case("${LOADER_RS_INTERPOLATION_UNDEFINED", "Unterminated variable reference in '${LOADER_RS_INTERPOLATION_UNDEFINED'")]
+    fn test_interpolate_strict_errors(string: &str, expected_message: &str) {
+        assert!(matches!(
+            EnvInterpolator::strict().interpolate(string).unwrap_err(),
+            LoaderError::FormatError(message) if message == expected_message
+        ));
+    }
+}
@@ -0,0 +1,532 @@
+mod document_index;
+mod fragment;
+mod interpolation;
+#[cfg(feature = "json-path")]
+mod path;
+#[cfg(feature = "json-schema")]
+mod schema;
+
+pub use crate::json::{
+    fragment::JsonFragment,
+    interpolation::{EnvInterpolator, Interpolate},
+};
+
+#[cfg(feature = "json-path")]
+use crate::json::path::JsonPath;
+#[cfg(feature = "json-schema")]
+use crate::json::schema::SchemaValidator;
+#[cfg(feature = "file-watcher")]
+use crate::loader::watcher::FileWatcher;
+use crate::{
+    json::document_index::{DocumentIndex, IndexedResource},
+    loader::{
+        archive::Archive,
+        credentials::CredentialsProvider,
+        dependencies::DocumentDependencies,
+        error::LoaderError,
+        expiration::ExpirationPolicy,
+        metadata::FetchMetadata,
+        refresh::Refresh,
+        timeout::Timeouts,
+        trait_::{GetCache, GetClient, LoadHook, LoaderTrait},
+        Loader,
+    },
+    thread_safe_cache::{ThreadSafeCacheImpl, ThreadSafeCacheTrait},
+    url_helpers::{json_pointer_from_reference_tokens, json_pointer_reference_tokens, remove_fragment_from_url, UrlError},
+};
+use json_trait_rs::JsonType;
+#[cfg(feature = "json-path")]
+use percent_encoding::percent_decode_str;
+#[cfg(feature = "json-schema")]
+use regex::Regex;
+use reqwest::blocking::Client;
+use std::sync::Arc;
+use url::Url;
+
+/// Loader of JSON-like documents, which supports JSON pointers and plain-name URL fragments.
+///
+/// `F` distinguishes loaders of different formats producing the same JSON type (ie. TOML documents loaded as `serde_json::Value`),
+/// and holds the format specific options (if any).
+#[derive(Debug)]
+pub struct ConcreteJsonLoader<T: JsonType, F = ()> {
+    loader: Loader<T>,
+    pub(in crate) format: F,
+    schema_resources: bool,
+    // Resources and plain-name fragments defined by the documents, per (final) document URL
+    indexes: ThreadSafeCacheImpl<Url, DocumentIndex<T>>,
+    // Fragments of the documents, keyed by the full URL (if the loader has a fragment cache)
+    fragments: ThreadSafeCacheImpl<Url, JsonFragment<T>>,
+    env_interpolator: Option<EnvInterpolator>,
+    #[cfg(feature = "json-schema")]
+    schema_url: Option<Url>,
+    // Regular expressions of the schemas, compiled once
+    #[cfg(feature = "json-schema")]
+    schema_patterns: ThreadSafeCacheImpl<String, Regex>,
+}
+
+impl<T: JsonType, F: Default> Default for ConcreteJsonLoader<T, F> {
+    fn default() -> Self {
+        Self::from(Loader::default())
+    }
+}
+
+impl<T: JsonType, F: Default> From<Loader<T>> for ConcreteJsonLoader<T, F> {
+    fn from(loader: Loader<T>) -> Self {
+        Self {
+            loader,
+            format: F::default(),
+            schema_resources: false,
+            indexes: ThreadSafeCacheImpl::default(),
+            fragments: ThreadSafeCacheImpl::default(),
+            env_interpolator: None,
+            #[cfg(feature = "json-schema")]
+            schema_url: None,
+            #[cfg(feature = "json-schema")]
+            schema_patterns: ThreadSafeCacheImpl::default(),
+        }
+    }
+}
+
+impl<T: JsonType, F> ConcreteJsonLoader<T, F> {
+    /// Format of the loaded documents, alongside its options (if any).
+    pub const fn format(&self) -> &F {
+        &self.format
+    }
+
+    /// Interpolate environment variables into the string values of the loaded documents, before caching them.
+    #[must_use]
+    pub fn with_env_interpolation(mut self, env_interpolator: EnvInterpolator) -> Self {
+        self.env_interpolator = Some(env_interpolator);
+        self
+    }
+
+    /// Resolve plain-name fragments (ie. `#foo`) via the anchors (`$anchor`, `$dynamicAnchor` and `$id` in the `#name` form)
+    /// defined by the loaded documents, as done by [JSON Schema](https://json-schema.org/) documents.
+    ///
+    /// Documents are indexed once, on the first lookup of a plain-name fragment. Plain-name fragments are rejected otherwise.
+    #[must_use]
+    pub const fn with_schema_resources(mut self) -> Self {
+        self.schema_resources = true;
+        self
+    }
+
+    /// Validate the loaded documents against the [JSON Schema](https://json-schema.org/) identified by `schema_url`, before caching them.
+    ///
+    /// The schema (and the schemas it references) is loaded, without being validated, via the loader itself so it shares its cache.
+    /// Schemas using keywords that are not supported (ie. `unevaluatedProperties` or `$dynamicRef`) fail the validation of the documents.
+    #[cfg(feature = "json-schema")]
+    #[must_use]
+    pub fn with_schema(mut self, schema_url: Url) -> Self {
+        self.schema_url = Some(schema_url);
+        self
+    }
+
+    /// Record that the document cached as `document_url` is built on top of the one cached as `dependency_url`.
+    #[cfg(feature = "trait_serde_yaml")]
+    pub(in crate) fn add_dependency(&self, document_url: &Url, dependency_url: &Url) {
+        if let Some(dependencies) = self.get_dependencies() {
+            dependencies.add(document_url, dependency_url);
+        }
+    }
+
+    /// Invalidate the cached document identified by `key`, alongside its index, the fragments extracted from it and the
+    /// documents built on top of it.
+    pub(in crate) fn invalidate_json_loader(&self, key: &Url) {
+        let fragmentless_url = remove_fragment_from_url(key);
+        if let Some(metadata) = self.get_fetch_metadata_cache().and_then(|fetch_metadata_cache| fetch_metadata_cache.get(&fragmentless_url)) {
+            let _d = self.indexes.remove(&metadata.url);
+        }
+        let _d = self.indexes.remove(&fragmentless_url);
+        let _d = self.fragments.remove(key);
+        self.remove_from_caches(key);
+        for dependent_url in self.get_dependencies().map_or_else(Vec::new, |dependencies| dependencies.remove(&fragmentless_url)) {
+            self.invalidate_json_loader(&dependent_url);
+        }
+    }
+}
+
+impl<T: ToOwnedJsonType + Interpolate, F> ConcreteJsonLoader<T, F>
+where
+    Self: LoaderTrait<T>,
+{
+    /// Interpolate environment variables and validate the loaded document (if configured), and then run the user-registered hooks.
+    pub(in crate) fn post_load_json_loader(&self, url: &Url, mut value: T) -> Result<T, LoaderError> {
+        if let Some(env_interpolator) = self.env_interpolator {
+            value.interpolate_strings(env_interpolator)?;
+        }
+        #[cfg(feature = "json-schema")]
+        if let Some(schema_url) = &self.schema_url {
+            self.validate_against_schema(url, schema_url, &value)?;
+        }
+        self.get_hooks().iter().try_fold(value, |value, hook| hook(url, value))
+    }
+}
+
+impl<T: JsonType, F> GetCache<T> for ConcreteJsonLoader<T, F> {
+    fn get_cache(&self) -> &dyn ThreadSafeCacheTrait<Url, T> {
+        self.loader.get_cache()
+    }
+
+    fn get_fragment_cache(&self) -> Option<&dyn ThreadSafeCacheTrait<Url, T>> {
+        self.loader.get_fragment_cache()
+    }
+
+    fn get_fetch_metadata_cache(&self) -> Option<&dyn ThreadSafeCacheTrait<Url, FetchMetadata>> {
+        self.loader.get_fetch_metadata_cache()
+    }
+
+    fn get_archives_cache(&self) -> Option<&dyn ThreadSafeCacheTrait<Url, Archive>> {
+        self.loader.get_archives_cache()
+    }
+
+    fn get_expiration_policy(&self) -> Option<&ExpirationPolicy> {
+        self.loader.get_expiration_policy()
+    }
+
+    fn get_refreshes_cache(&self) -> Option<Arc<dyn ThreadSafeCacheTrait<Url, Refresh> + Send + Sync>> {
+        self.loader.get_refreshes_cache()
+    }
+
+    #[cfg(feature = "file-watcher")]
+    fn get_file_watcher(&self) -> Option<&FileWatcher> {
+        self.loader.get_file_watcher()
+    }
+
+    fn get_hooks(&self) -> &[LoadHook<T>] {
+        self.loader.get_hooks()
+    }
+
+    fn get_dependencies(&self) -> Option<&DocumentDependencies> {
+        self.loader.get_dependencies()
+    }
+}
+
+impl<T: JsonType, F> GetClient<T> for ConcreteJsonLoader<T, F> {
+    fn get_client(&self) -> &Client {
+        self.loader.get_client()
+    }
+
+    fn get_credentials_provider(&self) -> Option<&CredentialsProvider> {
+        self.loader.get_credentials_provider()
+    }
+
+    fn get_timeouts(&self, url: &Url) -> Timeouts {
+        self.loader.get_timeouts(url)
+    }
+
+    fn get_client_with_timeouts(&self, timeouts: &Timeouts) -> Result<Client, LoaderError> {
+        self.loader.get_client_with_timeouts(timeouts)
+    }
+
+    fn get_max_content_size(&self) -> usize {
+        self.loader.get_max_content_size()
+    }
+}
+
+pub trait ToOwnedJsonType: JsonType {
+    fn to_owned_json_type(&self) -> Self;
+}
+
+impl<T: JsonType + Clone> ToOwnedJsonType for T {
+    fn to_owned_json_type(&self) -> Self {
+        self.clone()
+    }
+}
+
+/// Resolve a single JSON pointer reference token against `value`.
+///
+/// As defined by [RFC 6901](https://tools.ietf.org/html/rfc6901#section-4) arrays are indexed only by
+/// non negative base-10 integers without leading zeros (`-` refers to a non existing element).
+fn resolve_reference_token<'json, T: JsonType>(value: &'json T, token: &str) -> Option<&'json T> {
+    if value.is_array() {
+        if token == "0" || (!token.starts_with('0') && !token.is_empty() && token.bytes().all(|byte| byte.is_ascii_digit())) {
+            token.parse().ok().and_then(|index| value.get_index(index))
+        } else {
+            None
+        }
+    } else {
+        value.get_attribute(token)
+    }
+}
+
+fn resolve_reference_tokens<'json, T: JsonType>(value: &'json T, reference_tokens: &[String], fragment: &str) -> Result<&'json T, LoaderError> {
+    let mut current_value = value;
+    for (position, reference_token) in reference_tokens.iter().enumerate() {
+        current_value = resolve_reference_token(current_value, reference_token).ok_or_else(|| {
+            LoaderError::InvalidURL(UrlError::JsonFragmentError(format!(
+                "Token '{}' (position {}) of JSON pointer '{}' not found in '{}'",
+                reference_token,
+                position,
+                fragment,
+                json_pointer_from_reference_tokens(&reference_tokens[..position])
+            )))
+        })?;
+    }
+    Ok(current_value)
+}
+
+/// Reference tokens of the value identified by `fragment`, which is either a JSON pointer or a plain-name fragment
+/// defined into `resource`, relative to the document embedding `resource`.
+fn resource_reference_tokens(resource: &IndexedResource, fragment: &str) -> Result<Vec<String>, LoaderError> {
+    let fragment_reference_tokens = if fragment.is_empty() || fragment.starts_with('/') {
+        json_pointer_reference_tokens(fragment)?
+    } else {
+        resource.anchors.get(fragment).cloned().ok_or_else(|| {
+            LoaderError::InvalidURL(UrlError::JsonFragmentError(format!(
+                "Anchor '{}' not found in '{}'",
+                fragment,
+                resource.urls.first().map_or("", Url::as_str)
+            )))
+        })?
+    };
+    Ok(resource.reference_tokens.iter().cloned().chain(fragment_reference_tokens).collect())
+}
+
+impl<T: ToOwnedJsonType, F> ConcreteJsonLoader<T, F> {
+    /// Save the document in cache. Its resources are indexed on demand, so only once a plain-name fragment is looked up.
+    pub(in crate) fn save_in_cache_json_loader(&self, key: &Url, value: &Arc<T>) {
+        self.get_cache().set(key, value.clone());
+    }
+
+    /// Index of the document identified by `key`, built once per cached version of the document.
+    fn document_index(&self, key: &Url, document: &Arc<T>) -> Arc<DocumentIndex<T>> {
+        let fragmentless_url = remove_fragment_from_url(key);
+        if let Some(document_index) = self.indexes.get(&fragmentless_url).filter(|document_index| document_index.is_index_of(document)) {
+            return document_index;
+        }
+        let document_index = Arc::new(DocumentIndex::new(&fragmentless_url, document.clone()));
+        self.indexes.set(&fragmentless_url, document_index.clone());
+        document_index
+    }
+
+    /// Resolve `fragment`, which is either a JSON pointer or (with schema resources enabled) a plain-name fragment defined
+    /// into the document identified by `key`, into the reference tokens of the identified value and the value itself.
+    fn resolve_fragment<'json>(&self, key: &Url, fragment: &str, document: &'json Arc<T>) -> Result<(Vec<String>, &'json T), LoaderError> {
+        let reference_tokens = if fragment.is_empty() || fragment.starts_with('/') {
+            json_pointer_reference_tokens(fragment)?
+        } else if self.schema_resources {
+            resource_reference_tokens(self.document_index(key, document).root(), fragment)?
+        } else {
+            return Err(LoaderError::InvalidURL(UrlError::JsonFragmentError(format!(
+                "Plain-name fragment '{}' of '{}' is resolved only with schema resources enabled",
+                fragment, key
+            ))));
+        };
+
+        let value = resolve_reference_tokens(&**document, &reference_tokens, fragment)?;
+        Ok((reference_tokens, value))
+    }
+
+    /// Extract the value identified by `fragment`, which is either a JSON pointer or (with schema resources enabled)
+    /// a plain-name fragment defined into the document identified by `key`.
+    ///
+    /// Values within the document are copied, as they are returned in their own allocation (see
+    /// `get_or_fetch_fragment_with_result` to share the allocation of the document instead).
+    pub(in crate) fn extract_fragment_json_loader(&self, key: &Url, fragment: &str, value: Arc<T>) -> Result<Arc<T>, LoaderError> {
+        let (reference_tokens, fragment_value) = self.resolve_fragment(key, fragment, &value)?;
+        if reference_tokens.is_empty() {
+            Ok(value)
+        } else {
+            Ok(Arc::new(fragment_value.to_owned_json_type()))
+        }
+    }
+}
+
+impl<T: ToOwnedJsonType, F> ConcreteJsonLoader<T, F>
+where
+    Self: LoaderTrait<T>,
+{
+    /// Retrieve the value identified by `url` among the resources (identified by `$id`) embedded into the document identified
+    /// by `document_url`, as done while resolving [JSON Schema](https://json-schema.org/) references.
+    ///
+    /// Embedded resources are visible only via the document embedding them, so `url` is retrieved as any other document if not
+    /// defined within it. Plain-name fragments are resolved against the anchors of the identified resource.
+    pub fn get_or_fetch_resource_with_result(&self, document_url: &Url, url: &Url) -> Result<Arc<T>, LoaderError> {
+        self.get_or_fetch_resource(document_url, url, &|document_url| self.get_or_fetch_with_result(document_url))
+            .map(|(_, value)| value)
+    }
+
+    /// Retrieve the value identified by `url` within the document identified by `document_url` (or within the document
+    /// identified by `url` itself if not embedded into it), alongside the URL of the document defining it.
+    fn get_or_fetch_resource(&self, document_url: &Url, url: &Url, get_or_fetch_document: &dyn Fn(&Url) -> Result<Arc<T>, LoaderError>) -> Result<(Url, Arc<T>), LoaderError> {
+        let document_url = remove_fragment_from_url(document_url);
+        let document = get_or_fetch_document(&document_url)?;
+        let document_index = self.document_index(&self.get_final_url(&document_url), &document);
+
+        let (document_url, document_index) = if document_index.resource(url).is_some() {
+            (document_url, document_index)
+        } else {
+            let document_url = remove_fragment_from_url(url);
+            let document = get_or_fetch_document(&document_url)?;
+            let document_index = self.document_index(&self.get_final_url(&document_url), &document);
+            (document_url, document_index)
+        };
+        // The document was requested via `url` if the resource is not found, so it is identified by it even if redirected
+        let resource = document_index.resource(url).unwrap_or_else(|| document_index.root());
+
+        let fragment = url.fragment().unwrap_or("");
+        let reference_tokens = resource_reference_tokens(resource, fragment)?;
+        let document = document_index.document();
+        let value = if reference_tokens.is_empty() {
+            document.clone()
+        } else {
+            Arc::new(resolve_reference_tokens(&**document, &reference_tokens, fragment)?.to_owned_json_type())
+        };
+        Ok((document_url, value))
+    }
+}
+
+impl<T: ToOwnedJsonType, F> ConcreteJsonLoader<T, F>
+where
+    Self: LoaderTrait<T>,
+{
+    /// Retrieve the value identified by `key` as a [`JsonFragment`], which shares the allocation of the (cached) document
+    /// containing it.
+    ///
+    /// Fragments are cached, keyed by the full URL, only if the loader has a fragment cache (see `Loader::with_fragment_cache`),
+    /// such that repeated lookups do not resolve them again.
+    pub fn get_or_fetch_fragment_with_result(&self, key: &Url) -> Result<Arc<JsonFragment<T>>, LoaderError> {
+        self.invalidate_outdated(key);
+        let cache_fragment = key.fragment().is_some() && self.get_fragment_cache().is_some();
+        if let Some(fragment) = self.fragments.get(key).filter(|_| cache_fragment) {
+            return Ok(fragment);
+        }
+
+        let fragmentless_url = remove_fragment_from_url(key);
+        let document = self.get_or_fetch_with_result(&fragmentless_url)?;
+        let (reference_tokens, _) = self.resolve_fragment(&self.get_final_url(&fragmentless_url), key.fragment().unwrap_or(""), &document)?;
+        let fragment = Arc::new(JsonFragment::new(document, reference_tokens));
+        if cache_fragment {
+            self.fragments.set(key, fragment.clone());
+            // Cached fragments are invalidated alongside their document
+            if let Some(dependencies) = self.get_dependencies() {
+                dependencies.add(key, &fragmentless_url);
+            }
+        }
+        Ok(fragment)
+    }
+}
+
+#[cfg(feature = "json-path")]
+impl<T: ToOwnedJsonType, F> ConcreteJsonLoader<T, F>
+where
+    Self: LoaderTrait<T>,
+{
+    /// Evaluate the [JSONPath](https://goessner.net/articles/JsonPath/) `expression` against the document identified by `url`
+    /// (its fragment is ignored), returning the matched values in document order.
+    pub fn query(&self, url: &Url, expression: &str) -> Result<Vec<Arc<T>>, LoaderError> {
+        let json_path = JsonPath::parse(expression)?;
+        let document = self.get_or_fetch_with_result(&remove_fragment_from_url(url))?;
+        Ok(json_path.evaluate(&*document).into_iter().map(|value| Arc::new(value.to_owned_json_type())).collect())
+    }
+
+    /// Evaluate the fragment of `url` as [JSONPath](https://goessner.net/articles/JsonPath/) expression
+    /// (ie. `https://example.com/config.json#$.servers[*].url`), returning the matched values in document order.
+    ///
+    /// URLs without fragment are evaluated as the root identifier (`$`).
+    pub fn query_url(&self, url: &Url) -> Result<Vec<Arc<T>>, LoaderError> {
+        let fragment = url.fragment().unwrap_or("$");
+        let expression = percent_decode_str(fragment)
+            .decode_utf8()
+            .map_err(|utf8_error| UrlError::InvalidJsonPath(format!("Fragment '{}' is not a valid UTF-8 string: {}", fragment, utf8_error)))?;
+        self.query(url, &expression)
+    }
+}
+
+#[cfg(feature = "json-schema")]
+impl<T: ToOwnedJsonType, F> ConcreteJsonLoader<T, F>
+where
+    Self: LoaderTrait<T>,
+{
+    /// Retrieve the schema document identified by `key`, sharing the cache of the loaded documents.
+    ///
+    /// Schemas are not post-processed, as they are not documents to validate.
+    fn get_or_fetch_schema_document(&self, key: &Url) -> Result<Arc<T>, LoaderError> {
+        let fragmentless_url = remove_fragment_from_url(key);
+        if let Some(schema) = self.get_from_cache(&fragmentless_url) {
+            return Ok(schema);
+        }
+        let fetched_content = self.fetch(&fragmentless_url, self.get_timeouts(&fragmentless_url).total)?;
+        let schema = Arc::new(self.load_from_bytes(fetched_content.content.as_slice())?);
+        self.save_fetch_metadata(&fragmentless_url, &fetched_content.metadata);
+        self.save_in_cache(&fragmentless_url, &schema);
+        Ok(schema)
+    }
+
+    fn validate_against_schema(&self, url: &Url, schema_url: &Url, value: &T) -> Result<(), LoaderError> {
+        let get_or_fetch_schema_document = |document_url: &Url| self.get_or_fetch_schema_document(document_url);
+        // Documents are cached under the URLs they were requested via, while the relative references of the schemas are resolved
+        // against the URLs they were finally retrieved from
+        let resolve_schema = |document_url: &Url, schema_url: &Url| {
+            self.get_or_fetch_resource(document_url, schema_url, &get_or_fetch_schema_document)
+                .map(|(document_url, schema)| (document_url, self.get_final_url(schema_url), schema))
+        };
+        let violations = SchemaValidator::new(&resolve_schema, &self.schema_patterns).validate(schema_url, value)?;
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(LoaderError::ValidationError(format!(
+                "{} is not valid against {}: {}",
+                url,
+                schema_url,
+                violations.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")
+            )))
+        }
+    }
+}
+
+#[cfg(all(test, feature = "trait_serde_json"))]
+mod tests {
+    use super::ConcreteJsonLoader;
+    use crate::{loader::error::LoaderError, url_helpers::UrlError};
+    use serde_json::Value;
+    use std::sync::Arc;
+    use test_case::test_case;
+    use url::Url;
+
+    fn extract_json_pointer(fragment: &str, value: &Value) -> Result<Arc<Value>, LoaderError> {
+        ConcreteJsonLoader::<Value>::default().extract_fragment_json_loader(&Url::parse("memory:///").unwrap(), fragment, Arc::new(value.clone()))
+    }
+
+    lazy_static::lazy_static! {
+        static ref DOCUMENT: Value = json!({
+            "": "empty key",
+            "a": {"": "nested empty key"},
+            "a/b": "slash",
+            "m~n": "tilde",
+            "a b": "space",
+            "list": ["first", "second"],
+        });
+    }
+
+    #[test_case("" => Ok(Arc::new(DOCUMENT.clone())) ; "whole_document")]
+    #[test_case("/" => Ok(Arc::new(json!("empty key"))) ; "empty_key")]
+    #[test_case("/a/" => Ok(Arc::new(json!("nested empty key"))) ; "nested_empty_key")]
+    #[test_case("/a~1b" => Ok(Arc::new(json!("slash"))) ; "escaped_slash")]
+    #[test_case("/m~0n" => Ok(Arc::new(json!("tilde"))) ; "escaped_tilde")]
+    #[test_case("/a%20b" => Ok(Arc::new(json!("space"))) ; "percent_encoded")]
+    #[test_case("/list/1" => Ok(Arc::new(json!("second"))) ; "array_index")]
+    fn test_extract_json_pointer_valid(fragment: &str) -> Result<Arc<Value>, String> {
+        extract_json_pointer(fragment, &*DOCUMENT).map_err(|loader_error| loader_error.to_string())
+    }
+
+    #[test_case("/missing", "Token 'missing' (position 0) of JSON pointer '/missing' not found in ''")]
+    #[test_case("/a/missing", "Token 'missing' (position 1) of JSON pointer '/a/missing' not found in '/a'")]
+    #[test_case("/list/01", "Token '01' (position 1) of JSON pointer '/list/01' not found in '/list'")]
+    #[test_case("/list/-", "Token '-' (position 1) of JSON pointer '/list/-' not found in '/list'")]
+    #[test_case("/a~1b/key", "Token 'key' (position 1) of JSON pointer '/a~1b/key' not found in '/a~1b'")]
+    fn test_extract_json_pointer_not_found(fragment: &str, expected_message: &str) {
+        assert!(matches!(
+            extract_json_pointer(fragment, &*DOCUMENT).unwrap_err(),
+            LoaderError::InvalidURL(UrlError::JsonFragmentError(message)) if message == expected_message
+        ));
+    }
+
+    #[test]
+    fn test_extract_json_pointer_invalid_pointer() {
+        assert!(matches!(
+            extract_json_pointer("/a~", &*DOCUMENT).unwrap_err(),
+            LoaderError::InvalidURL(UrlError::InvalidJsonPointer(_))
+        ));
+    }
+}
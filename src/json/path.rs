@@ -0,0 +1,356 @@
+use crate::url_helpers::UrlError;
+use json_trait_rs::{JsonMapTrait, JsonType};
+use std::{iter::Peekable, str::Chars};
+
+#[derive(Debug, PartialEq)]
+enum Selector {
+    Name(String),
+    Wildcard,
+    Index(i64),
+    Slice(Option<i64>, Option<i64>, Option<i64>),
+}
+
+#[derive(Debug, PartialEq)]
+struct Segment {
+    descendants: bool,
+    selectors: Vec<Selector>,
+}
+
+/// Parsed [JSONPath](https://goessner.net/articles/JsonPath/) expression.
+///
+/// The supported syntax covers the root identifier (`$`), child and descendant segments (`.name`, `..name`, `[...]`, `..[...]`)
+/// with name (`'name'`), wildcard (`*`), index (`-1`) and slice (`start:end:step`) selectors. Filter expressions are not supported.
+#[derive(Debug, PartialEq)]
+pub(in crate) struct JsonPath {
+    segments: Vec<Segment>,
+}
+
+struct Parser<'expression> {
+    expression: &'expression str,
+    chars: Peekable<Chars<'expression>>,
+}
+
+impl<'expression> Parser<'expression> {
+    fn error(&self, message: &str) -> UrlError {
+        UrlError::InvalidJsonPath(format!("{} in JSONPath expression '{}'", message, self.expression))
+    }
+
+    fn skip_whitespaces(&mut self) {
+        while self.chars.peek().map_or(false, |chr| chr.is_whitespace()) {
+            let _d = self.chars.next();
+        }
+    }
+
+    fn expect(&mut self, expected_chr: char) -> Result<(), UrlError> {
+        self.skip_whitespaces();
+        if self.chars.next() == Some(expected_chr) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("Expected '{}'", expected_chr)))
+        }
+    }
+
+    fn parse(mut self) -> Result<JsonPath, UrlError> {
+        if self.chars.next() != Some('$') {
+            return Err(self.error("Expected root identifier '$'"));
+        }
+
+        let mut segments = Vec::new();
+        while let Some(chr) = self.chars.next() {
+            let segment = match chr {
+                '.' if self.chars.peek() == Some(&'.') => {
+                    let _d = self.chars.next();
+                    if self.chars.peek() == Some(&'[') {
+                        let _d = self.chars.next();
+                        Segment {
+                            descendants: true,
+                            selectors: self.parse_bracketed_selectors()?,
+                        }
+                    } else {
+                        Segment {
+                            descendants: true,
+                            selectors: vec![self.parse_shorthand_selector()?],
+                        }
+                    }
+                }
+                '.' => Segment {
+                    descendants: false,
+                    selectors: vec![self.parse_shorthand_selector()?],
+                },
+                '[' => Segment {
+                    descendants: false,
+                    selectors: self.parse_bracketed_selectors()?,
+                },
+                chr if chr.is_whitespace() => continue,
+                _ => return Err(self.error(&format!("Unexpected character '{}'", chr))),
+            };
+            segments.push(segment);
+        }
+        Ok(JsonPath { segments })
+    }
+
+    fn parse_shorthand_selector(&mut self) -> Result<Selector, UrlError> {
+        if self.chars.peek() == Some(&'*') {
+            let _d = self.chars.next();
+            return Ok(Selector::Wildcard);
+        }
+
+        let mut name = String::new();
+        while let Some(chr) = self.chars.peek() {
+            if chr.is_alphanumeric() || *chr == '_' || *chr == '-' || *chr == '$' {
+                name.push(*chr);
+                let _d = self.chars.next();
+            } else {
+                break;
+            }
+        }
+        if name.is_empty() {
+            Err(self.error("Expected member name"))
+        } else {
+            Ok(Selector::Name(name))
+        }
+    }
+
+    fn parse_bracketed_selectors(&mut self) -> Result<Vec<Selector>, UrlError> {
+        let mut selectors = Vec::new();
+        loop {
+            self.skip_whitespaces();
+            selectors.push(match self.chars.peek() {
+                Some('\'') | Some('"') => Selector::Name(self.parse_string_literal()?),
+                Some('*') => {
+                    let _d = self.chars.next();
+                    Selector::Wildcard
+                }
+                Some('?') => return Err(self.error("Filter expressions are not supported")),
+                _ => self.parse_index_or_slice()?,
+            });
+            self.skip_whitespaces();
+            match self.chars.next() {
+                Some(',') => {}
+                Some(']') => return Ok(selectors),
+                _ => return Err(self.error("Expected ',' or ']'")),
+            }
+        }
+    }
+
+    fn parse_string_literal(&mut self) -> Result<String, UrlError> {
+        let quote = self.chars.next();
+        let mut value = String::new();
+        loop {
+            match self.chars.next() {
+                Some('\\') => match self.chars.next() {
+                    Some('b') => value.push('\u{8}'),
+                    Some('f') => value.push('\u{c}'),
+                    Some('n') => value.push('\n'),
+                    Some('r') => value.push('\r'),
+                    Some('t') => value.push('\t'),
+                    Some(chr) => value.push(chr),
+                    None => return Err(self.error("Unterminated string literal")),
+                },
+                Some(chr) if Some(chr) == quote => return Ok(value),
+                Some(chr) => value.push(chr),
+                None => return Err(self.error("Unterminated string literal")),
+            }
+        }
+    }
+
+    fn parse_integer(&mut self) -> Result<Option<i64>, UrlError> {
+        self.skip_whitespaces();
+        let mut integer = String::new();
+        while let Some(chr) = self.chars.peek() {
+            if chr.is_ascii_digit() || (*chr == '-' && integer.is_empty()) {
+                integer.push(*chr);
+                let _d = self.chars.next();
+            } else {
+                break;
+            }
+        }
+        if integer.is_empty() {
+            Ok(None)
+        } else {
+            integer.parse().map(Some).map_err(|_| self.error(&format!("Invalid integer '{}'", integer)))
+        }
+    }
+
+    fn parse_index_or_slice(&mut self) -> Result<Selector, UrlError> {
+        let start = self.parse_integer()?;
+        self.skip_whitespaces();
+        if self.chars.peek() != Some(&':') {
+            return start.map(Selector::Index).ok_or_else(|| self.error("Expected selector"));
+        }
+        self.expect(':')?;
+        let end = self.parse_integer()?;
+        self.skip_whitespaces();
+        let step = if self.chars.peek() == Some(&':') {
+            self.expect(':')?;
+            self.parse_integer()?
+        } else {
+            None
+        };
+        Ok(Selector::Slice(start, end, step))
+    }
+}
+
+fn normalize_index(index: i64, length: i64) -> i64 {
+    if index >= 0 {
+        index
+    } else {
+        length + index
+    }
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap, clippy::cast_sign_loss)]
+fn slice_indexes(length: usize, start: Option<i64>, end: Option<i64>, step: Option<i64>) -> Vec<usize> {
+    let length = length as i64;
+    let step = step.unwrap_or(1);
+    let mut indexes = Vec::new();
+    if step > 0 {
+        let lower = normalize_index(start.unwrap_or(0), length).max(0).min(length);
+        let upper = normalize_index(end.unwrap_or(length), length).max(0).min(length);
+        let mut index = lower;
+        while index < upper {
+            indexes.push(index as usize);
+            // Steps could be as large as i64::MAX, so overflowing indexes are past the end of the slice
+            index = match index.checked_add(step) {
+                Some(next_index) => next_index,
+                None => break,
+            };
+        }
+    } else if step < 0 {
+        let upper = start.map_or(length - 1, |start| normalize_index(start, length)).max(-1).min(length - 1);
+        let lower = end.map_or(-1, |end| normalize_index(end, length)).max(-1).min(length - 1);
+        let mut index = upper;
+        while lower < index {
+            indexes.push(index as usize);
+            index = match index.checked_add(step) {
+                Some(next_index) => next_index,
+                None => break,
+            };
+        }
+    }
+    indexes
+}
+
+impl Selector {
+    #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap, clippy::cast_sign_loss)]
+    fn select<'json, T: JsonType>(&self, value: &'json T, selected_values: &mut Vec<&'json T>) {
+        match self {
+            Self::Name(name) => selected_values.extend(value.get_attribute(name)),
+            Self::Wildcard => {
+                if let Some(object) = value.as_object() {
+                    selected_values.extend(object.keys().filter_map(|key| value.get_attribute(key)));
+                } else if let Some(items) = value.as_array() {
+                    selected_values.extend(items);
+                }
+            }
+            Self::Index(index) => {
+                if let Some(items) = value.as_array() {
+                    let index = normalize_index(*index, items.len() as i64);
+                    if index >= 0 {
+                        selected_values.extend(value.get_index(index as usize));
+                    }
+                }
+            }
+            Self::Slice(start, end, step) => {
+                if let Some(items) = value.as_array() {
+                    selected_values.extend(slice_indexes(items.len(), *start, *end, *step).into_iter().filter_map(|index| value.get_index(index)));
+                }
+            }
+        }
+    }
+}
+
+fn collect_descendants<'json, T: JsonType>(value: &'json T, descendants: &mut Vec<&'json T>) {
+    descendants.push(value);
+    if let Some(object) = value.as_object() {
+        for child in object.keys().filter_map(|key| value.get_attribute(key)) {
+            collect_descendants(child, descendants);
+        }
+    } else if let Some(items) = value.as_array() {
+        for item in items {
+            collect_descendants(item, descendants);
+        }
+    }
+}
+
+impl JsonPath {
+    pub(in crate) fn parse(expression: &str) -> Result<Self, UrlError> {
+        Parser {
+            expression,
+            chars: expression.trim().chars().peekable(),
+        }
+        .parse()
+    }
+
+    /// Evaluate the expression against `value`, returning the matched values in document order.
+    pub(in crate) fn evaluate<'json, T: JsonType>(&self, value: &'json T) -> Vec<&'json T> {
+        let mut nodes = vec![value];
+        for segment in &self.segments {
+            let input_nodes = if segment.descendants {
+                let mut descendants = Vec::new();
+                for node in nodes {
+                    collect_descendants(node, &mut descendants);
+                }
+                descendants
+            } else {
+                nodes
+            };
+
+            nodes = Vec::new();
+            for node in input_nodes {
+                for selector in &segment.selectors {
+                    selector.select(node, &mut nodes);
+                }
+            }
+        }
+        nodes
+    }
+}
+
+#[cfg(all(test, feature = "trait_serde_json"))]
+mod tests {
+    use super::JsonPath;
+    use crate::url_helpers::UrlError;
+    use serde_json::Value;
+    use test_case::test_case;
+
+    lazy_static::lazy_static! {
+        static ref DOCUMENT: Value = json!({
+            "servers": [
+                {"url": "https://a.example.com", "tags": ["public"]},
+                {"url": "https://b.example.com", "tags": []},
+                {"url": "https://c.example.com"},
+            ],
+            "key with spaces": {"url": "nested"},
+        });
+    }
+
+    #[test_case("$" => vec![DOCUMENT.clone()] ; "root")]
+    #[test_case("$.servers[*].url" => vec![json!("https://a.example.com"), json!("https://b.example.com"), json!("https://c.example.com")] ; "wildcard")]
+    #[test_case("$.servers[0].url" => vec![json!("https://a.example.com")] ; "index")]
+    #[test_case("$.servers[-1].url" => vec![json!("https://c.example.com")] ; "negative_index")]
+    #[test_case("$.servers[5].url" => Vec::<Value>::new() ; "out_of_bound_index")]
+    #[test_case("$.servers[1:].url" => vec![json!("https://b.example.com"), json!("https://c.example.com")] ; "slice")]
+    #[test_case("$.servers[::-2].url" => vec![json!("https://c.example.com"), json!("https://a.example.com")] ; "slice_with_negative_step")]
+    #[test_case("$.servers[1:2:9223372036854775807].url" => vec![json!("https://b.example.com")] ; "slice_with_huge_step")]
+    #[test_case("$.servers[-1::-9223372036854775808].url" => vec![json!("https://c.example.com")] ; "slice_with_huge_negative_step")]
+    #[test_case("$.servers[0,2]['url']" => vec![json!("https://a.example.com"), json!("https://c.example.com")] ; "multiple_selectors")]
+    #[test_case("$['key with spaces'].url" => vec![json!("nested")] ; "quoted_name")]
+    #[test_case("$..url" => vec![json!("nested"), json!("https://a.example.com"), json!("https://b.example.com"), json!("https://c.example.com")] ; "descendants")]
+    #[test_case("$..tags[0]" => vec![json!("public")] ; "descendants_with_index")]
+    fn test_evaluate(expression: &str) -> Vec<Value> {
+        JsonPath::parse(expression).unwrap().evaluate(&*DOCUMENT).into_iter().cloned().collect()
+    }
+
+    #[test_case("servers" => "Expected root identifier '$' in JSONPath expression 'servers'")]
+    #[test_case("$.servers[?(@.url)]" => "Filter expressions are not supported in JSONPath expression '$.servers[?(@.url)]'")]
+    #[test_case("$.servers[0" => "Expected ',' or ']' in JSONPath expression '$.servers[0'")]
+    #[test_case("$['servers]" => "Unterminated string literal in JSONPath expression '$['servers]'")]
+    #[test_case("$." => "Expected member name in JSONPath expression '$.'")]
+    fn test_parse_invalid_expression(expression: &str) -> String {
+        match JsonPath::parse(expression).unwrap_err() {
+            UrlError::InvalidJsonPath(message) => message,
+            url_error => panic!("Expected UrlError::InvalidJsonPath, received {:?}", url_error),
+        }
+    }
+}
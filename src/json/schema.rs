@@ -0,0 +1,809 @@
+use crate::{
+    loader::error::LoaderError,
+    thread_safe_cache::{ThreadSafeCacheImpl, ThreadSafeCacheTrait},
+    url_helpers::json_pointer_from_reference_tokens,
+};
+use json_trait_rs::{JsonMapTrait, JsonType};
+use regex::Regex;
+use std::{
+    cell::RefCell,
+    convert::TryFrom,
+    fmt::{Display, Error, Formatter},
+    sync::Arc,
+};
+use url::Url;
+
+/// Keywords affecting the validation (see `SchemaValidator`).
+const VALIDATION_KEYWORDS: &[&str] = &[
+    "type",
+    "enum",
+    "const",
+    "minimum",
+    "maximum",
+    "exclusiveMinimum",
+    "exclusiveMaximum",
+    "multipleOf",
+    "minLength",
+    "maxLength",
+    "pattern",
+    "items",
+    "additionalItems",
+    "prefixItems",
+    "minItems",
+    "maxItems",
+    "uniqueItems",
+    "contains",
+    "minContains",
+    "maxContains",
+    "properties",
+    "patternProperties",
+    "additionalProperties",
+    "propertyNames",
+    "required",
+    "dependentRequired",
+    "dependentSchemas",
+    "dependencies",
+    "minProperties",
+    "maxProperties",
+    "allOf",
+    "anyOf",
+    "oneOf",
+    "not",
+    "if",
+    "then",
+    "else",
+    "$ref",
+];
+
+/// Keywords not affecting the validation: identifiers, annotations and definitions of the referenced schemas.
+const ANNOTATION_KEYWORDS: &[&str] = &[
+    "$schema",
+    "$id",
+    "$anchor",
+    "$dynamicAnchor",
+    "$recursiveAnchor",
+    "$vocabulary",
+    "$comment",
+    "$defs",
+    "definitions",
+    "title",
+    "description",
+    "default",
+    "examples",
+    "deprecated",
+    "readOnly",
+    "writeOnly",
+    "format",
+    "contentEncoding",
+    "contentMediaType",
+    "contentSchema",
+];
+
+/// Keywords affecting the validation that are not supported (see `SchemaValidator`).
+const UNSUPPORTED_KEYWORDS: &[&str] = &["unevaluatedProperties", "unevaluatedItems", "$dynamicRef", "$recursiveRef"];
+
+/// Violation of a JSON Schema keyword, located by the JSON pointer of the invalid value.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(in crate) struct Violation {
+    pub(in crate) instance_pointer: String,
+    pub(in crate) message: String,
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        write!(f, "#{}: {}", self.instance_pointer, self.message)
+    }
+}
+
+/// Location of a schema: the document defining it, and the URL its relative references are resolved against.
+struct Scope {
+    document_url: Url,
+    base_url: Url,
+}
+
+impl Scope {
+    /// Scope of `schema`, defined within this scope, rejecting the schemas using keywords that are not supported.
+    ///
+    /// Unknown keywords are ignored (ie. the `x-` extensions), unless they belong to the core vocabulary (`$` prefixed).
+    fn nested<T: JsonType>(&self, schema: &T) -> Result<Self, LoaderError> {
+        if let Some(object) = schema.as_object() {
+            if let Some(keyword) = object.keys().find(|keyword| {
+                UNSUPPORTED_KEYWORDS.contains(keyword) || (keyword.starts_with('$') && !VALIDATION_KEYWORDS.contains(keyword) && !ANNOTATION_KEYWORDS.contains(keyword))
+            }) {
+                return Err(LoaderError::ValidationError(format!(
+                    "Unsupported JSON Schema keyword \"{}\" in {}",
+                    keyword, self.base_url
+                )));
+            }
+        }
+        Ok(Self {
+            document_url: self.document_url.clone(),
+            base_url: schema
+                .get_attribute("$id")
+                .and_then(JsonType::as_string)
+                .and_then(|id| self.base_url.join(id).ok())
+                .unwrap_or_else(|| self.base_url.clone()),
+        })
+    }
+}
+
+/// Resolver of the schema identified by an URL, within the document identified by an URL, returning the URL of the document defining it
+/// and the URL the relative references of the schema are resolved against (which differ if the document was redirected).
+pub(in crate) type ResolveSchema<'resolver, T> = dyn Fn(&Url, &Url) -> Result<(Url, Url, Arc<T>), LoaderError> + 'resolver;
+
+/// Validator of JSON-like documents against [JSON Schema](https://json-schema.org/)s.
+///
+/// The supported keywords are the structural ones (`type`, `enum`, `const`), the numeric, string, array and object
+/// constraints (`prefixItems` and the array form of `items` included), the conditional ones (`if`, `then`, `else`,
+/// `dependentRequired`, `dependentSchemas` and `dependencies`), the combinators (`allOf`, `anyOf`, `oneOf`, `not`) and
+/// `$ref`. Annotations (ie. `title`, `format` or `$comment`) and unknown keywords (ie. `x-` extensions or `nullable`) are
+/// ignored, while the schemas using the keywords that are not supported (`unevaluatedProperties`, `unevaluatedItems`,
+/// `$dynamicRef` and `$recursiveRef`) are rejected as the validation would silently ignore them.
+///
+/// References are resolved relatively to the (`$id` aware) URL of the referencing schema and retrieved via `resolve_schema`,
+/// which looks them up within the document defining the referencing schema first (as it could embed the referenced resource).
+pub(in crate) struct SchemaValidator<'resolver, T> {
+    resolve_schema: &'resolver ResolveSchema<'resolver, T>,
+    patterns: &'resolver ThreadSafeCacheImpl<String, Regex>,
+    // References being evaluated, alongside the JSON pointer of the instance they are evaluated for
+    evaluated_references: RefCell<Vec<(Url, String)>>,
+}
+
+impl<'resolver, T: JsonType> SchemaValidator<'resolver, T> {
+    /// Create a validator resolving references via `resolve_schema` and caching the compiled regular expressions in `patterns`.
+    pub(in crate) fn new(resolve_schema: &'resolver ResolveSchema<'resolver, T>, patterns: &'resolver ThreadSafeCacheImpl<String, Regex>) -> Self {
+        Self {
+            resolve_schema,
+            patterns,
+            evaluated_references: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Validate `instance` against the schema identified by `schema_url`, returning the violations sorted by location.
+    pub(in crate) fn validate(&self, schema_url: &Url, instance: &T) -> Result<Vec<Violation>, LoaderError> {
+        let (document_url, base_url, schema) = (self.resolve_schema)(schema_url, schema_url)?;
+        let scope = Scope { document_url, base_url };
+        let mut violations = Vec::new();
+        self.validate_value(&scope, &*schema, instance, &mut Vec::new(), &mut violations)?;
+        violations.sort();
+        Ok(violations)
+    }
+
+    fn is_valid(&self, scope: &Scope, schema: &T, instance: &T) -> Result<bool, LoaderError> {
+        let mut violations = Vec::new();
+        self.validate_value(scope, schema, instance, &mut Vec::new(), &mut violations)?;
+        Ok(violations.is_empty())
+    }
+
+    fn validate_value(&self, scope: &Scope, schema: &T, instance: &T, reference_tokens: &mut Vec<String>, violations: &mut Vec<Violation>) -> Result<(), LoaderError> {
+        if let Some(allowed) = schema.as_boolean() {
+            if !allowed {
+                add_violation(violations, reference_tokens, "Value is not allowed by the false schema".to_string());
+            }
+            return Ok(());
+        }
+        let scope = scope.nested(schema)?;
+
+        if let Some(types) = schema.get_attribute("type").map(schema_types) {
+            if !types.is_empty() && !types.iter().any(|type_name| is_of_type(instance, type_name)) {
+                add_violation(violations, reference_tokens, format!("Value is not of type {}", quote_all(&types, " or ")));
+            }
+        }
+        if let Some(mut allowed_values) = schema.get_attribute("enum").and_then(JsonType::as_array) {
+            if !allowed_values.any(|allowed_value| json_equals(allowed_value, instance)) {
+                add_violation(violations, reference_tokens, "Value is not one of the enumerated values".to_string());
+            }
+        }
+        if let Some(constant) = schema.get_attribute("const") {
+            if !json_equals(constant, instance) {
+                add_violation(violations, reference_tokens, "Value is not equal to the constant".to_string());
+            }
+        }
+
+        self.validate_scalar_constraints(schema, instance, reference_tokens, violations)?;
+        self.validate_array_constraints(&scope, schema, instance, reference_tokens, violations)?;
+        self.validate_object_constraints(&scope, schema, instance, reference_tokens, violations)?;
+
+        self.validate_combinators(&scope, schema, instance, reference_tokens, violations)?;
+        if let Some(reference) = schema.get_attribute("$ref").and_then(JsonType::as_string) {
+            let instance_pointer = json_pointer_from_reference_tokens(reference_tokens);
+            self.with_referenced_schema(&scope, reference, instance_pointer, |reference_scope, referenced_schema| {
+                self.validate_value(reference_scope, referenced_schema, instance, reference_tokens, violations)
+            })?;
+        }
+
+        self.validate_items(&scope, schema, instance, reference_tokens, violations)?;
+        self.validate_properties(&scope, schema, instance, reference_tokens, violations)
+    }
+
+    /// Run `validate` on the schema referenced by `reference`, for the instance located by `instance_pointer`.
+    ///
+    /// Evaluating again a reference for the same instance location would never terminate, so it fails instead.
+    fn with_referenced_schema<R>(
+        &self,
+        scope: &Scope,
+        reference: &str,
+        instance_pointer: String,
+        validate: impl FnOnce(&Scope, &T) -> Result<R, LoaderError>,
+    ) -> Result<R, LoaderError> {
+        let reference_url = scope.base_url.join(reference)?;
+        let evaluated_reference = (reference_url.clone(), instance_pointer);
+        if self.evaluated_references.borrow().contains(&evaluated_reference) {
+            return Err(LoaderError::ValidationError(format!(
+                "Circular $ref {} evaluated for #{}",
+                reference_url, evaluated_reference.1
+            )));
+        }
+
+        let (document_url, base_url, referenced_schema) = (self.resolve_schema)(&scope.document_url, &reference_url)?;
+        self.evaluated_references.borrow_mut().push(evaluated_reference);
+        let result = validate(&Scope { document_url, base_url }, &*referenced_schema);
+        let _d = self.evaluated_references.borrow_mut().pop();
+        result
+    }
+
+    fn validate_combinators(&self, scope: &Scope, schema: &T, instance: &T, reference_tokens: &mut Vec<String>, violations: &mut Vec<Violation>) -> Result<(), LoaderError> {
+        if let Some(all_of) = schema.get_attribute("allOf").and_then(JsonType::as_array) {
+            for sub_schema in all_of {
+                self.validate_value(scope, sub_schema, instance, reference_tokens, violations)?;
+            }
+        }
+        if let Some(any_of) = schema.get_attribute("anyOf").and_then(JsonType::as_array) {
+            if !self.count_valid(scope, any_of, instance)?.iter().any(|valid| *valid) {
+                add_violation(violations, reference_tokens, "Value does not match any of the \"anyOf\" schemas".to_string());
+            }
+        }
+        if let Some(one_of) = schema.get_attribute("oneOf").and_then(JsonType::as_array) {
+            let valid_count = self.count_valid(scope, one_of, instance)?.into_iter().filter(|valid| *valid).count();
+            if valid_count != 1 {
+                add_violation(
+                    violations,
+                    reference_tokens,
+                    format!("Value matches {} of the \"oneOf\" schemas instead of exactly one", valid_count),
+                );
+            }
+        }
+        if let Some(not) = schema.get_attribute("not") {
+            if self.is_valid(scope, not, instance)? {
+                add_violation(violations, reference_tokens, "Value matches the \"not\" schema".to_string());
+            }
+        }
+        if let Some(if_schema) = schema.get_attribute("if") {
+            let branch_schema = if self.is_valid(scope, if_schema, instance)? {
+                schema.get_attribute("then")
+            } else {
+                schema.get_attribute("else")
+            };
+            if let Some(branch_schema) = branch_schema {
+                self.validate_value(scope, branch_schema, instance, reference_tokens, violations)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn count_valid<'json>(&self, scope: &Scope, schemas: Box<dyn ExactSizeIterator<Item = &'json T> + 'json>, instance: &T) -> Result<Vec<bool>, LoaderError>
+    where
+        T: 'json,
+    {
+        schemas.map(|sub_schema| self.is_valid(scope, sub_schema, instance)).collect()
+    }
+
+    fn validate_scalar_constraints(&self, schema: &T, instance: &T, reference_tokens: &[String], violations: &mut Vec<Violation>) -> Result<(), LoaderError> {
+        if let Some(number) = instance.as_number() {
+            let limit = |keyword: &str| schema.get_attribute(keyword).and_then(JsonType::as_number);
+            if let Some(minimum) = limit("minimum").filter(|minimum| number < *minimum) {
+                add_violation(violations, reference_tokens, format!("Value is less than the minimum of {}", minimum));
+            }
+            if let Some(maximum) = limit("maximum").filter(|maximum| number > *maximum) {
+                add_violation(violations, reference_tokens, format!("Value is greater than the maximum of {}", maximum));
+            }
+            if let Some(minimum) = limit("exclusiveMinimum").filter(|minimum| number <= *minimum) {
+                add_violation(violations, reference_tokens, format!("Value is less than or equal to the exclusive minimum of {}", minimum));
+            }
+            if let Some(maximum) = limit("exclusiveMaximum").filter(|maximum| number >= *maximum) {
+                add_violation(
+                    violations,
+                    reference_tokens,
+                    format!("Value is greater than or equal to the exclusive maximum of {}", maximum),
+                );
+            }
+            if let Some(multiple_of) = limit("multipleOf").filter(|multiple_of| *multiple_of > 0.0 && !is_multiple_of(number, *multiple_of)) {
+                add_violation(violations, reference_tokens, format!("Value is not a multiple of {}", multiple_of));
+            }
+        }
+
+        if let Some(string) = instance.as_string() {
+            let length = string.chars().count();
+            if let Some(min_length) = get_size(schema, "minLength").filter(|min_length| length < *min_length) {
+                add_violation(violations, reference_tokens, format!("String is shorter than {} characters", min_length));
+            }
+            if let Some(max_length) = get_size(schema, "maxLength").filter(|max_length| length > *max_length) {
+                add_violation(violations, reference_tokens, format!("String is longer than {} characters", max_length));
+            }
+            if let Some(pattern) = schema.get_attribute("pattern").and_then(JsonType::as_string) {
+                if !self.compile_pattern(pattern)?.is_match(string) {
+                    add_violation(violations, reference_tokens, format!("String does not match the pattern \"{}\"", pattern));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn validate_array_constraints(&self, scope: &Scope, schema: &T, instance: &T, reference_tokens: &[String], violations: &mut Vec<Violation>) -> Result<(), LoaderError> {
+        let items: Vec<&T> = match instance.as_array() {
+            Some(items) => items.collect(),
+            None => return Ok(()),
+        };
+        if let Some(min_items) = get_size(schema, "minItems").filter(|min_items| items.len() < *min_items) {
+            add_violation(violations, reference_tokens, format!("Array has less than {} items", min_items));
+        }
+        if let Some(max_items) = get_size(schema, "maxItems").filter(|max_items| items.len() > *max_items) {
+            add_violation(violations, reference_tokens, format!("Array has more than {} items", max_items));
+        }
+        if schema.get_attribute("uniqueItems").and_then(JsonType::as_boolean) == Some(true)
+            && items
+                .iter()
+                .enumerate()
+                .any(|(index, item)| items[index + 1..].iter().any(|other_item| json_equals(*item, *other_item)))
+        {
+            add_violation(violations, reference_tokens, "Array items are not unique".to_string());
+        }
+        if let Some(contains) = schema.get_attribute("contains") {
+            let matching_count = items.iter().try_fold(0, |matching_count, item| {
+                Ok::<_, LoaderError>(matching_count + usize::from(self.is_valid(scope, contains, item)?))
+            })?;
+            match get_size(schema, "minContains").unwrap_or(1) {
+                1 if matching_count == 0 => {
+                    add_violation(violations, reference_tokens, "Array does not contain any item matching the \"contains\" schema".to_string());
+                }
+                min_contains if matching_count < min_contains => add_violation(
+                    violations,
+                    reference_tokens,
+                    format!("Array contains less than {} items matching the \"contains\" schema", min_contains),
+                ),
+                _ => {}
+            }
+            if let Some(max_contains) = get_size(schema, "maxContains").filter(|max_contains| matching_count > *max_contains) {
+                add_violation(
+                    violations,
+                    reference_tokens,
+                    format!("Array contains more than {} items matching the \"contains\" schema", max_contains),
+                );
+            }
+        }
+        Ok(())
+    }
+
+    fn validate_object_constraints(&self, scope: &Scope, schema: &T, instance: &T, reference_tokens: &mut Vec<String>, violations: &mut Vec<Violation>) -> Result<(), LoaderError> {
+        let object = match instance.as_object() {
+            Some(object) => object,
+            None => return Ok(()),
+        };
+        let properties_count = object.keys().count();
+        if let Some(min_properties) = get_size(schema, "minProperties").filter(|min_properties| properties_count < *min_properties) {
+            add_violation(violations, reference_tokens, format!("Object has less than {} properties", min_properties));
+        }
+        if let Some(max_properties) = get_size(schema, "maxProperties").filter(|max_properties| properties_count > *max_properties) {
+            add_violation(violations, reference_tokens, format!("Object has more than {} properties", max_properties));
+        }
+        if let Some(required) = schema.get_attribute("required").and_then(JsonType::as_array) {
+            for property_name in required.filter_map(JsonType::as_string) {
+                if !instance.has_attribute(property_name) {
+                    add_violation(violations, reference_tokens, format!("Required property \"{}\" is missing", property_name));
+                }
+            }
+        }
+
+        // `dependencies` is the keyword superseded by `dependentRequired` (array values) and `dependentSchemas` (schema values)
+        let dependencies = ["dependentRequired", "dependentSchemas", "dependencies"]
+            .iter()
+            .filter_map(|keyword| schema.get_attribute(keyword).and_then(JsonType::as_object));
+        for dependencies_object in dependencies {
+            for (property_name, dependency) in dependencies_object.items() {
+                if !instance.has_attribute(property_name) {
+                    continue;
+                }
+                if let Some(required) = dependency.as_array() {
+                    for required_property_name in required.filter_map(JsonType::as_string) {
+                        if !instance.has_attribute(required_property_name) {
+                            add_violation(
+                                violations,
+                                reference_tokens,
+                                format!("Property \"{}\" is required by property \"{}\"", required_property_name, property_name),
+                            );
+                        }
+                    }
+                } else {
+                    self.validate_value(scope, dependency, instance, reference_tokens, violations)?;
+                }
+            }
+        }
+
+        if let Some(property_names) = schema.get_attribute("propertyNames") {
+            for property_name in object.keys() {
+                let mut property_reference_tokens = reference_tokens.clone();
+                property_reference_tokens.push(property_name.to_string());
+                let instance_pointer = json_pointer_from_reference_tokens(&property_reference_tokens);
+                if !self.is_valid_property_name(scope, property_names, property_name, &instance_pointer)? {
+                    add_violation(
+                        violations,
+                        reference_tokens,
+                        format!("Property name \"{}\" does not match the \"propertyNames\" schema", property_name),
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether the property name `name`, of the property located by `instance_pointer`, is valid against `schema`.
+    ///
+    /// Names are not values of type `T`, so only the keywords applicable to strings are evaluated.
+    fn is_valid_property_name(&self, scope: &Scope, schema: &T, name: &str, instance_pointer: &str) -> Result<bool, LoaderError> {
+        if let Some(allowed) = schema.as_boolean() {
+            return Ok(allowed);
+        }
+        let scope = scope.nested(schema)?;
+        let is_valid = |sub_schema: &T| self.is_valid_property_name(&scope, sub_schema, name, instance_pointer);
+
+        let length = name.chars().count();
+        if !schema
+            .get_attribute("type")
+            .map(schema_types)
+            .map_or(true, |types| types.is_empty() || types.contains(&"string"))
+            || !schema
+                .get_attribute("enum")
+                .and_then(JsonType::as_array)
+                .map_or(true, |mut allowed_values| allowed_values.any(|allowed_value| allowed_value.as_string() == Some(name)))
+            || !schema.get_attribute("const").map_or(true, |constant| constant.as_string() == Some(name))
+            || !get_size(schema, "minLength").map_or(true, |min_length| length >= min_length)
+            || !get_size(schema, "maxLength").map_or(true, |max_length| length <= max_length)
+        {
+            return Ok(false);
+        }
+        if let Some(pattern) = schema.get_attribute("pattern").and_then(JsonType::as_string) {
+            if !self.compile_pattern(pattern)?.is_match(name) {
+                return Ok(false);
+            }
+        }
+
+        if let Some(all_of) = schema.get_attribute("allOf").and_then(JsonType::as_array) {
+            for sub_schema in all_of {
+                if !is_valid(sub_schema)? {
+                    return Ok(false);
+                }
+            }
+        }
+        if let Some(any_of) = schema.get_attribute("anyOf").and_then(JsonType::as_array) {
+            if !any_of.map(is_valid).collect::<Result<Vec<_>, _>>()?.contains(&true) {
+                return Ok(false);
+            }
+        }
+        if let Some(one_of) = schema.get_attribute("oneOf").and_then(JsonType::as_array) {
+            if one_of.map(is_valid).collect::<Result<Vec<_>, _>>()?.into_iter().filter(|valid| *valid).count() != 1 {
+                return Ok(false);
+            }
+        }
+        if let Some(not) = schema.get_attribute("not") {
+            if is_valid(not)? {
+                return Ok(false);
+            }
+        }
+        if let Some(if_schema) = schema.get_attribute("if") {
+            let branch_schema = if is_valid(if_schema)? {
+                schema.get_attribute("then")
+            } else {
+                schema.get_attribute("else")
+            };
+            if let Some(branch_schema) = branch_schema {
+                if !is_valid(branch_schema)? {
+                    return Ok(false);
+                }
+            }
+        }
+        match schema.get_attribute("$ref").and_then(JsonType::as_string) {
+            Some(reference) => self.with_referenced_schema(&scope, reference, instance_pointer.to_string(), |reference_scope, referenced_schema| {
+                self.is_valid_property_name(reference_scope, referenced_schema, name, instance_pointer)
+            }),
+            None => Ok(true),
+        }
+    }
+
+    fn validate_items(&self, scope: &Scope, schema: &T, instance: &T, reference_tokens: &mut Vec<String>, violations: &mut Vec<Violation>) -> Result<(), LoaderError> {
+        let items = match instance.as_array() {
+            Some(items) => items,
+            None => return Ok(()),
+        };
+        let items_schema = schema.get_attribute("items");
+        // `prefixItems` supersedes the array form of `items`, whose remaining items are validated against `additionalItems`
+        let (prefix_schemas, remaining_items_schema): (Vec<&T>, Option<&T>) =
+            match (schema.get_attribute("prefixItems").and_then(JsonType::as_array), items_schema.and_then(JsonType::as_array)) {
+                (Some(prefix_items), _) => (prefix_items.collect(), items_schema),
+                (None, Some(tuple_items)) => (tuple_items.collect(), schema.get_attribute("additionalItems")),
+                (None, None) => (Vec::new(), items_schema),
+            };
+
+        for (index, item) in items.enumerate() {
+            if let Some(item_schema) = prefix_schemas.get(index).copied().or(remaining_items_schema) {
+                reference_tokens.push(index.to_string());
+                let result = self.validate_value(scope, item_schema, item, reference_tokens, violations);
+                let _d = reference_tokens.pop();
+                result?;
+            }
+        }
+        Ok(())
+    }
+
+    fn validate_properties(&self, scope: &Scope, schema: &T, instance: &T, reference_tokens: &mut Vec<String>, violations: &mut Vec<Violation>) -> Result<(), LoaderError> {
+        let object = match instance.as_object() {
+            Some(object) => object,
+            None => return Ok(()),
+        };
+        let properties = schema.get_attribute("properties");
+        let pattern_properties_object = schema.get_attribute("patternProperties").and_then(JsonType::as_object);
+        let pattern_properties = pattern_properties_object.as_ref().map_or_else(
+            || Ok(Vec::new()),
+            |pattern_properties_object| {
+                pattern_properties_object
+                    .items()
+                    .map(|(pattern, property_schema)| Ok((self.compile_pattern(pattern)?, property_schema)))
+                    .collect::<Result<Vec<_>, LoaderError>>()
+            },
+        )?;
+        let additional_properties = schema.get_attribute("additionalProperties");
+
+        for (property_name, property_value) in object.items() {
+            let mut property_schemas: Vec<&T> = properties.and_then(|properties| properties.get_attribute(property_name)).into_iter().collect();
+            property_schemas.extend(
+                pattern_properties
+                    .iter()
+                    .filter(|(pattern, _)| pattern.is_match(property_name))
+                    .map(|(_, property_schema)| *property_schema),
+            );
+            if property_schemas.is_empty() {
+                property_schemas.extend(additional_properties);
+            }
+
+            reference_tokens.push(property_name.to_string());
+            let result = property_schemas
+                .into_iter()
+                .try_for_each(|property_schema| self.validate_value(scope, property_schema, property_value, reference_tokens, violations));
+            let _d = reference_tokens.pop();
+            result?;
+        }
+        Ok(())
+    }
+
+    /// Compile `pattern`, once per loader, as schemas are evaluated for every loaded document.
+    fn compile_pattern(&self, pattern: &str) -> Result<Arc<Regex>, LoaderError> {
+        let pattern = pattern.to_string();
+        if let Some(regex) = self.patterns.get(&pattern) {
+            return Ok(regex);
+        }
+        let regex = Arc::new(Regex::new(&pattern).map_err(|regex_error| LoaderError::ValidationError(format!("Invalid pattern \"{}\": {}", pattern, regex_error)))?);
+        self.patterns.set(&pattern, regex.clone());
+        Ok(regex)
+    }
+}
+
+fn add_violation(violations: &mut Vec<Violation>, reference_tokens: &[String], message: String) {
+    violations.push(Violation {
+        instance_pointer: json_pointer_from_reference_tokens(reference_tokens),
+        message,
+    });
+}
+
+fn schema_types<T: JsonType>(type_: &T) -> Vec<&str> {
+    match type_.as_array() {
+        Some(items) => items.filter_map(JsonType::as_string).collect(),
+        None => type_.as_string().into_iter().collect(),
+    }
+}
+
+/// Digits and exponent of the shortest decimal representation of `number` (ie. `(15, -1)` for `-1.5`), ignoring its sign.
+fn decimal_parts(number: f64) -> (u64, i32) {
+    let formatted = format!("{:e}", number.abs());
+    let (mantissa, exponent) = formatted.split_once('e').unwrap_or((&formatted, "0"));
+    let (integer_part, fractional_part) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let digits = format!("{}{}", integer_part, fractional_part).parse().unwrap_or(0);
+    let exponent = exponent.parse::<i32>().unwrap_or(0) - i32::try_from(fractional_part.len()).unwrap_or(0);
+    (digits, exponent)
+}
+
+/// Whether `number` is a multiple of `multiple_of` (a positive number).
+///
+/// Dividing floating point numbers is not exact (ie. `0.3 / 0.1` is `2.9999999999999996`), so the numbers are compared via
+/// their shortest decimal representations, which are the ones written in the documents.
+fn is_multiple_of(number: f64, multiple_of: f64) -> bool {
+    let (number_digits, number_exponent) = decimal_parts(number);
+    let (multiple_of_digits, multiple_of_exponent) = decimal_parts(multiple_of);
+    if number_digits == 0 {
+        return true;
+    }
+    let (number_digits, multiple_of_digits) = (u128::from(number_digits), u128::from(multiple_of_digits));
+    match u32::try_from(number_exponent - multiple_of_exponent) {
+        // number_digits * 10^exponent_difference modulo multiple_of_digits, without overflowing
+        Ok(exponent_difference) => (0..exponent_difference).fold(number_digits % multiple_of_digits, |remainder, _| remainder * 10 % multiple_of_digits) == 0,
+        Err(_) => u32::try_from(multiple_of_exponent - number_exponent)
+            .ok()
+            .and_then(|exponent_difference| 10_u128.checked_pow(exponent_difference))
+            .and_then(|power| power.checked_mul(multiple_of_digits))
+            .map_or(false, |divisor| number_digits % divisor == 0),
+    }
+}
+
+fn quote_all(strings: &[&str], separator: &str) -> String {
+    strings.iter().map(|string| format!("\"{}\"", string)).collect::<Vec<_>>().join(separator)
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn get_size<T: JsonType>(schema: &T, keyword: &str) -> Option<usize> {
+    schema
+        .get_attribute(keyword)
+        .and_then(JsonType::as_integer)
+        .filter(|size| *size >= 0)
+        .map(|size| size as usize)
+}
+
+fn is_of_type<T: JsonType>(value: &T, type_name: &str) -> bool {
+    match type_name {
+        "array" => value.is_array(),
+        "boolean" => value.is_boolean(),
+        "integer" => value.is_integer() || value.as_number().map_or(false, |number| number.fract() == 0.0),
+        "null" => value.is_null(),
+        "number" => value.is_number(),
+        "object" => value.is_object(),
+        "string" => value.is_string(),
+        _ => false,
+    }
+}
+
+#[allow(clippy::float_cmp)]
+fn json_equals<T: JsonType>(value: &T, other_value: &T) -> bool {
+    if let (Some(object), Some(other_object)) = (value.as_object(), other_value.as_object()) {
+        object.keys().count() == other_object.keys().count()
+            && object.items().all(|(key, property_value)| {
+                other_value
+                    .get_attribute(key)
+                    .map_or(false, |other_property_value| json_equals(property_value, other_property_value))
+            })
+    } else if let (Some(items), Some(other_items)) = (value.as_array(), other_value.as_array()) {
+        items.len() == other_items.len() && items.zip(other_items).all(|(item, other_item)| json_equals(item, other_item))
+    } else if let (Some(number), Some(other_number)) = (value.as_number(), other_value.as_number()) {
+        match (value.as_integer(), other_value.as_integer()) {
+            (Some(integer), Some(other_integer)) => integer == other_integer,
+            _ => number == other_number,
+        }
+    } else {
+        (value.is_null() && other_value.is_null())
+            || value.as_boolean().map_or(false, |boolean| other_value.as_boolean() == Some(boolean))
+            || value.as_string().map_or(false, |string| other_value.as_string() == Some(string))
+    }
+}
+
+#[cfg(all(test, feature = "trait_serde_json"))]
+mod tests {
+    use super::{is_multiple_of, SchemaValidator};
+    use crate::{loader::error::LoaderError, thread_safe_cache::ThreadSafeCacheImpl};
+    use serde_json::Value;
+    use std::sync::Arc;
+    use test_case::test_case;
+    use url::Url;
+
+    fn validate(schema: &Value, instance: &Value) -> Result<Vec<String>, LoaderError> {
+        let schema_url = Url::parse("memory:///schema.json").unwrap();
+        let resolve_schema = |document_url: &Url, url: &Url| -> Result<(Url, Url, Arc<Value>), LoaderError> {
+            let sub_schema = url.fragment().map_or(Some(schema), |fragment| schema.pointer(fragment));
+            sub_schema
+                .map(|sub_schema| (document_url.clone(), url.clone(), Arc::new(sub_schema.clone())))
+                .ok_or(LoaderError::UnknownError)
+        };
+        let patterns = ThreadSafeCacheImpl::default();
+        let violations = SchemaValidator::new(&resolve_schema, &patterns).validate(&schema_url, instance)?;
+        Ok(violations.into_iter().map(|violation| violation.to_string()).collect())
+    }
+
+    #[test_case(&json![true], &json![1] => Vec::<String>::new() ; "true_schema")]
+    #[test_case(&json![false], &json![1] => vec!["#: Value is not allowed by the false schema"] ; "false_schema")]
+    #[test_case(&json![{"type": "integer"}], &json![1.0] => Vec::<String>::new() ; "integral_number_is_integer")]
+    #[test_case(&json![{"type": ["string", "null"]}], &json![1] => vec!["#: Value is not of type \"string\" or \"null\""] ; "types")]
+    #[test_case(&json![{"enum": [1, {"a": [true]}]}], &json![{"a": [true]}] => Vec::<String>::new() ; "enum")]
+    #[test_case(&json![{"const": "a"}], &json!["b"] => vec!["#: Value is not equal to the constant"] ; "const")]
+    #[test_case(&json![{"minimum": 2, "exclusiveMaximum": 1, "multipleOf": 2}], &json![1] => vec![
+        "#: Value is greater than or equal to the exclusive maximum of 1",
+        "#: Value is less than the minimum of 2",
+        "#: Value is not a multiple of 2",
+    ] ; "numbers")]
+    #[test_case(&json![{"minLength": 3, "pattern": "^[a-z]+$"}], &json!["é1"] => vec![
+        "#: String does not match the pattern \"^[a-z]+$\"",
+        "#: String is shorter than 3 characters",
+    ] ; "strings")]
+    #[test_case(&json![{"items": [{"type": "string"}], "additionalItems": {"type": "integer"}, "uniqueItems": true}], &json![["a", 1, "b", 1]] => vec![
+        "#: Array items are not unique",
+        "#/2: Value is not of type \"integer\"",
+    ] ; "arrays")]
+    #[test_case(&json![{"contains": {"const": 1}, "maxItems": 1}], &json![[2, 3]] => vec![
+        "#: Array does not contain any item matching the \"contains\" schema",
+        "#: Array has more than 1 items",
+    ] ; "array_contains")]
+    #[test_case(&json![{
+        "required": ["a", "b"],
+        "properties": {"a": {"type": "string"}},
+        "patternProperties": {"^x-": {"type": "integer"}},
+        "additionalProperties": false,
+    }], &json![{"a": 1, "x-a/b": "1", "c": null}] => vec![
+        "#: Required property \"b\" is missing",
+        "#/a: Value is not of type \"string\"",
+        "#/c: Value is not allowed by the false schema",
+        "#/x-a~1b: Value is not of type \"integer\"",
+    ] ; "objects")]
+    #[test_case(&json![{"anyOf": [{"type": "string"}, {"type": "null"}], "oneOf": [{"type": "number"}, {"type": "integer"}], "not": {"const": 1}}], &json![1] => vec![
+        "#: Value does not match any of the \"anyOf\" schemas",
+        "#: Value matches 2 of the \"oneOf\" schemas instead of exactly one",
+        "#: Value matches the \"not\" schema",
+    ] ; "combinators")]
+    #[test_case(&json![{"$defs": {"positive": {"exclusiveMinimum": 0}}, "items": {"$ref": "#/$defs/positive"}}], &json![[1, 0]] => vec![
+        "#/1: Value is less than or equal to the exclusive minimum of 0",
+    ] ; "reference")]
+    #[test_case(&json![{"if": {"type": "integer"}, "then": {"minimum": 2}, "else": {"type": "string"}}], &json![1] => vec![
+        "#: Value is less than the minimum of 2",
+    ] ; "if_then")]
+    #[test_case(&json![{"if": {"type": "integer"}, "then": {"minimum": 2}, "else": {"type": "string"}}], &json![true] => vec![
+        "#: Value is not of type \"string\"",
+    ] ; "if_else")]
+    #[test_case(&json![{"dependentRequired": {"a": ["b"]}, "dependentSchemas": {"b": {"required": ["c"]}}}], &json![{"a": 1}] => vec![
+        "#: Property \"b\" is required by property \"a\"",
+    ] ; "dependent_keywords")]
+    #[test_case(&json![{"dependencies": {"a": ["b"], "c": {"required": ["d"]}}}], &json![{"a": 1, "c": 2}] => vec![
+        "#: Property \"b\" is required by property \"a\"",
+        "#: Required property \"d\" is missing",
+    ] ; "dependencies")]
+    #[test_case(&json![{"propertyNames": {"$ref": "#/$defs/name"}, "$defs": {"name": {"pattern": "^[a-z]+$", "not": {"const": "b"}}}}], &json![{"a": 1, "b": 2, "C": 3}] => vec![
+        "#: Property name \"C\" does not match the \"propertyNames\" schema",
+        "#: Property name \"b\" does not match the \"propertyNames\" schema",
+    ] ; "property_names")]
+    #[test_case(&json![{"prefixItems": [{"type": "string"}], "items": {"type": "integer"}}], &json![["a", 1, "b"]] => vec![
+        "#/2: Value is not of type \"integer\"",
+    ] ; "prefix_items")]
+    #[test_case(&json![{"contains": {"const": 1}, "minContains": 2, "maxContains": 2}], &json![[1, 2]] => vec![
+        "#: Array contains less than 2 items matching the \"contains\" schema",
+    ] ; "min_contains")]
+    #[test_case(&json![{"contains": {"const": 1}, "minContains": 0, "maxContains": 1}], &json![[1, 1]] => vec![
+        "#: Array contains more than 1 items matching the \"contains\" schema",
+    ] ; "max_contains")]
+    #[test_case(&json![{"type": "number", "multipleOf": 0.1}], &json![0.3] => Vec::<String>::new() ; "decimal_multiple_of")]
+    #[test_case(&json![{"title": "Title", "description": "", "$comment": "", "examples": [], "default": 1, "definitions": {}, "$schema": "", "$anchor": "a"}], &json![1] => Vec::<String>::new() ; "annotations")]
+    #[test_case(&json![{"type": "string", "format": "email", "x-internal": true, "nullable": true}], &json!["a"] => Vec::<String>::new() ; "format_and_unknown_keywords")]
+    #[test_case(&json![{"properties": {"child": {"$ref": "#"}}, "required": ["key"]}], &json![{"key": 1, "child": {"key": 2, "child": {}}}] => vec![
+        "#/child/child: Required property \"key\" is missing",
+    ] ; "recursive_reference")]
+    fn test_validate(schema: &Value, instance: &Value) -> Vec<String> {
+        validate(schema, instance).unwrap()
+    }
+
+    #[test_case(&json![{"$recursiveRef": "#"}], &json![1] => "Unsupported JSON Schema keyword \"$recursiveRef\" in memory:///schema.json" ; "recursive_reference_keyword")]
+    #[test_case(&json![{"$unknown": "#"}], &json![1] => "Unsupported JSON Schema keyword \"$unknown\" in memory:///schema.json" ; "unknown_core_keyword")]
+    #[test_case(&json![{"items": {"unevaluatedProperties": false}}], &json![[{}]] => "Unsupported JSON Schema keyword \"unevaluatedProperties\" in memory:///schema.json" ; "nested_unevaluated_properties")]
+    #[test_case(&json![{"$dynamicRef": "#meta"}], &json![1] => "Unsupported JSON Schema keyword \"$dynamicRef\" in memory:///schema.json" ; "dynamic_reference")]
+    #[test_case(&json![{"$defs": {"a": {"$ref": "#/$defs/b"}, "b": {"$ref": "#/$defs/a"}}, "$ref": "#/$defs/a"}], &json![1] => "Circular $ref memory:///schema.json#/$defs/a evaluated for #" ; "circular_reference")]
+    #[test_case(&json![{"propertyNames": {"$ref": "#/propertyNames"}}], &json![{"a": 1}] => "Circular $ref memory:///schema.json#/propertyNames evaluated for #/a" ; "circular_property_names_reference")]
+    #[test_case(&json![{"pattern": "("}], &json!["a"] => "Invalid pattern \"(\"" ; "invalid_pattern")]
+    fn test_validate_invalid_schema(schema: &Value, instance: &Value) -> String {
+        match validate(schema, instance).unwrap_err() {
+            LoaderError::ValidationError(message) => message.split(": ").next().unwrap().to_string(),
+            loader_error => panic!("Expected LoaderError::ValidationError, received {:?}", loader_error),
+        }
+    }
+
+    #[test_case(0.3, 0.1 => true ; "decimal")]
+    #[test_case(0.35, 0.1 => false ; "not_multiple_decimal")]
+    #[test_case(7.0, 0.5 => true ; "integer_of_decimal")]
+    #[test_case(1e300, 1e-300 => true ; "huge_ratio")]
+    #[test_case(1e-300, 3.0 => false ; "tiny_number")]
+    #[test_case(0.0, 3.0 => true ; "zero")]
+    #[test_case(-4.5, 1.5 => true ; "negative")]
+    #[test_case(9_007_199_254_740_993_f64, 2.0 => true ; "largest_integers")]
+    fn test_is_multiple_of(number: f64, multiple_of: f64) -> bool {
+        is_multiple_of(number, multiple_of)
+    }
+}
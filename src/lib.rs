@@ -48,7 +48,18 @@ extern crate derive_builder;
 
 #[macro_use]
 extern crate strum_macros;
-#[cfg(all(test, any(feature = "trait_json", feature = "trait_serde_json", feature = "trait_serde_yaml")))]
+#[cfg(all(
+    test,
+    any(
+        feature = "trait_cbor",
+        feature = "trait_json",
+        feature = "trait_json5",
+        feature = "trait_msgpack",
+        feature = "trait_serde_json",
+        feature = "trait_serde_yaml",
+        feature = "trait_toml"
+    )
+))]
 #[macro_use]
 extern crate serde_json;
 
@@ -63,8 +74,18 @@ pub mod traits;
 pub mod url_helpers;
 
 #[cfg(feature = "json-loader")]
-pub use crate::json::ConcreteJsonLoader;
+pub use crate::json::{ConcreteJsonLoader, EnvInterpolator, JsonFragment};
 pub use crate::{
-    loader::{error::LoaderError, trait_::LoaderTrait, Loader},
+    loader::{
+        client::{ClientConfig, RedirectPolicy},
+        credentials::CredentialsProvider,
+        error::LoaderError,
+        expiration::ExpirationPolicy,
+        metadata::{FetchMetadata, Loaded},
+        subscription::Subscription,
+        timeout::{TimeoutPolicy, Timeouts},
+        trait_::LoaderTrait,
+        Loader,
+    },
     traits::loaders,
 };
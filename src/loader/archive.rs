@@ -0,0 +1,206 @@
+#[cfg(feature = "archive-zip")]
+use crate::loader::decompression::read_to_end_with_limit;
+use crate::{
+    loader::error::LoaderError,
+    url_helpers::{remove_fragment_from_url, UrlError},
+};
+use percent_encoding::percent_decode_str;
+#[cfg(feature = "archive-tar")]
+use std::{collections::HashMap, convert::TryFrom};
+use std::{
+    fmt::{Debug, Error, Formatter},
+    io::ErrorKind,
+    ops::Range,
+};
+#[cfg(feature = "archive-zip")]
+use std::{io::Cursor, sync::Arc};
+use url::Url;
+
+const ENTRY_SEPARATOR: &str = "!/";
+const ZIP_MAGIC_BYTES: [&[u8]; 2] = [b"PK\x03\x04", b"PK\x05\x06"];
+const TAR_MAGIC_BYTES_RANGE: Range<usize> = 257..262;
+const TAR_MAGIC_BYTES: &[u8] = b"ustar";
+
+/// Split `url`, identifying an entry of an archive, into the URL of the archive and the path of the entry
+/// (`None` if `url` does not identify an archive entry).
+///
+/// Archive entries are identified by `jar:<archive URL>!/<entry path>` URLs, as well as by `zip+<archive URL>!/<entry path>`
+/// and `tar+<archive URL>!/<entry path>` URLs. Only the latter are hierarchical, so relative references within the
+/// archive could be resolved (ie. `zip+file:///bundle.zip!/schemas/a.json` and `b.json`).
+pub(in crate) fn split_archive_url(url: &Url) -> Result<Option<(Url, String)>, LoaderError> {
+    let scheme = url.scheme();
+    if scheme != "jar" && !scheme.starts_with("zip+") && !scheme.starts_with("tar+") {
+        return Ok(None);
+    }
+
+    let fragmentless_url = remove_fragment_from_url(url);
+    // `jar:`, `zip+` and `tar+` prefixes have the same length
+    let archive_entry = &fragmentless_url.as_str()[4..];
+    let (archive_url, entry_path) = archive_entry
+        .split_once(ENTRY_SEPARATOR)
+        .ok_or_else(|| UrlError::ArchiveEntryError(format!("Archive entry separator '{}' not found in '{}'", ENTRY_SEPARATOR, url)))?;
+    let entry_path = percent_decode_str(entry_path)
+        .decode_utf8()
+        .map_err(|utf8_error| UrlError::ArchiveEntryError(format!("Entry path of '{}' is not a valid UTF-8 string: {}", url, utf8_error)))?;
+    Ok(Some((Url::parse(archive_url)?, entry_path.to_string())))
+}
+
+#[allow(clippy::large_enum_variant)]
+enum ArchiveIndex {
+    #[cfg(feature = "archive-zip")]
+    Zip(zip::ZipArchive<Cursor<Arc<[u8]>>>),
+    // Entries of tar archives are stored uncompressed, so they are indexed by their position within the archive
+    #[cfg(feature = "archive-tar")]
+    Tar { content: Vec<u8>, entries: HashMap<String, Range<usize>> },
+}
+
+/// Zip or tar archive opened by the loaders, whose index is cached such that its entries are read on demand.
+///
+/// Compressed tar archives (ie. `.tar.gz`) are supported according to the enabled `decompression-*` features.
+pub struct Archive {
+    index: ArchiveIndex,
+}
+
+impl Debug for Archive {
+    #[cfg_attr(not(any(feature = "archive-zip", feature = "archive-tar")), allow(unused_variables))]
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self.index {
+            #[cfg(feature = "archive-zip")]
+            ArchiveIndex::Zip(ref zip_archive) => f.debug_struct("Archive").field("format", &"zip").field("entries", &zip_archive.len()).finish(),
+            #[cfg(feature = "archive-tar")]
+            ArchiveIndex::Tar { ref entries, .. } => f.debug_struct("Archive").field("format", &"tar").field("entries", &entries.len()).finish(),
+        }
+    }
+}
+
+impl Archive {
+    /// Open the (already decompressed) archive `content`, whose format is detected via its magic bytes.
+    pub(in crate) fn open(content: Vec<u8>) -> Result<Self, LoaderError> {
+        if ZIP_MAGIC_BYTES.iter().any(|magic_bytes| content.starts_with(magic_bytes)) {
+            #[cfg(feature = "archive-zip")]
+            return Self::open_zip(content);
+        } else if content.get(TAR_MAGIC_BYTES_RANGE) == Some(TAR_MAGIC_BYTES) {
+            #[cfg(feature = "archive-tar")]
+            return Self::open_tar(content);
+        }
+        Err(LoaderError::FormatError(
+            "Unsupported archive format (zip and tar archives are supported via the archive-zip and archive-tar features)".to_string(),
+        ))
+    }
+
+    #[cfg(feature = "archive-zip")]
+    fn open_zip(content: Vec<u8>) -> Result<Self, LoaderError> {
+        let zip_archive = zip::ZipArchive::new(Cursor::new(Arc::from(content))).map_err(|zip_error| LoaderError::FormatError(format!("Invalid zip archive: {}", zip_error)))?;
+        Ok(Self {
+            index: ArchiveIndex::Zip(zip_archive),
+        })
+    }
+
+    #[cfg(feature = "archive-tar")]
+    fn open_tar(content: Vec<u8>) -> Result<Self, LoaderError> {
+        let mut entries = HashMap::new();
+        for entry in tar::Archive::new(content.as_slice()).entries()? {
+            let entry = entry?;
+            if !entry.header().entry_type().is_file() {
+                continue;
+            }
+            let start = usize::try_from(entry.raw_file_position()).map_err(|error| LoaderError::from(&error))?;
+            let size = usize::try_from(entry.size()).map_err(|error| LoaderError::from(&error))?;
+            let _d = entries.insert(normalize_entry_path(&entry.path()?.to_string_lossy()).to_string(), start..start + size);
+        }
+        Ok(Self {
+            index: ArchiveIndex::Tar { content, entries },
+        })
+    }
+
+    /// Read the entry at `entry_path` of the archive, failing if it is larger than `max_size` bytes once decompressed.
+    #[cfg_attr(not(any(feature = "archive-zip", feature = "archive-tar")), allow(unused_variables))]
+    pub(in crate) fn read_entry(&self, entry_path: &str, max_size: usize) -> Result<Vec<u8>, LoaderError> {
+        let entry_path = normalize_entry_path(entry_path);
+        let not_found_error = || LoaderError::IOError(std::io::Error::new(ErrorKind::NotFound, format!("Entry '{}' not found in archive", entry_path)));
+        let too_large_error = || LoaderError::FormatError(format!("Entry '{}' of archive exceeds the maximum size of {} bytes", entry_path, max_size));
+        match self.index {
+            #[cfg(feature = "archive-zip")]
+            ArchiveIndex::Zip(ref zip_archive) => {
+                // Reading an entry requires a mutable archive, cloning it is cheap as it shares the index and the content
+                let mut zip_archive = zip_archive.clone();
+                let mut zip_file = zip_archive.by_name(entry_path).map_err(|zip_error| match zip_error {
+                    zip::result::ZipError::FileNotFound => not_found_error(),
+                    zip_error => LoaderError::FormatError(format!("Invalid zip entry '{}': {}", entry_path, zip_error)),
+                })?;
+                // The declared size of the entry could not be trusted, so the reads are bounded instead
+                read_to_end_with_limit(&mut zip_file, max_size)?.ok_or_else(too_large_error)
+            }
+            #[cfg(feature = "archive-tar")]
+            ArchiveIndex::Tar { ref content, ref entries } => {
+                let range = entries.get(entry_path).ok_or_else(not_found_error)?;
+                if range.len() > max_size {
+                    return Err(too_large_error());
+                }
+                // The ranges come from the headers of the archive, which could point past its end
+                content
+                    .get(range.clone())
+                    .map(<[u8]>::to_vec)
+                    .ok_or_else(|| LoaderError::FormatError(format!("Entry '{}' is out of the bounds of the archive", entry_path)))
+            }
+        }
+    }
+}
+
+// Entries are addressed relatively to the root of the archive
+fn normalize_entry_path(entry_path: &str) -> &str {
+    entry_path.trim_start_matches("./").trim_start_matches('/')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::split_archive_url;
+    #[cfg(all(feature = "archive-zip", feature = "archive-tar", feature = "decompression-gzip"))]
+    use super::Archive;
+    #[cfg(all(feature = "archive-zip", feature = "archive-tar", feature = "decompression-gzip"))]
+    use crate::{
+        loader::{
+            decompression::{decompress, DEFAULT_MAX_CONTENT_SIZE},
+            error::LoaderError,
+        },
+        testing_helpers::test_data_file_path,
+    };
+    use test_case::test_case;
+    use url::Url;
+
+    #[cfg(all(feature = "archive-zip", feature = "archive-tar", feature = "decompression-gzip"))]
+    fn open_archive(archive_file_name: &str) -> Archive {
+        let archive_url = Url::from_file_path(test_data_file_path(&[archive_file_name]).unwrap()).unwrap();
+        let content = std::fs::read(test_data_file_path(&[archive_file_name]).unwrap()).unwrap();
+        Archive::open(decompress(&archive_url, None, content, DEFAULT_MAX_CONTENT_SIZE).unwrap()).unwrap()
+    }
+
+    // schemas/Object.json is 25 bytes long
+    #[cfg(all(feature = "archive-zip", feature = "archive-tar", feature = "decompression-gzip"))]
+    #[test_case("Bundle.zip", 25 => Ok(25) ; "zip_entry")]
+    #[test_case("Bundle.zip", 24 => Err("Entry 'schemas/Object.json' of archive exceeds the maximum size of 24 bytes".to_string()) ; "zip_entry_exceeding_max_size")]
+    #[test_case("Bundle.tar.gz", 25 => Ok(25) ; "tar_entry")]
+    #[test_case("Bundle.tar.gz", 24 => Err("Entry 'schemas/Object.json' of archive exceeds the maximum size of 24 bytes".to_string()) ; "tar_entry_exceeding_max_size")]
+    fn test_read_entry_with_max_size(archive_file_name: &str, max_size: usize) -> Result<usize, String> {
+        match open_archive(archive_file_name).read_entry("schemas/Object.json", max_size) {
+            Ok(entry_content) => Ok(entry_content.len()),
+            Err(LoaderError::FormatError(message)) => Err(message),
+            Err(loader_error) => panic!("Expected LoaderError::FormatError, received {:?}", loader_error),
+        }
+    }
+
+    #[test_case("jar:file:///bundle.zip!/schemas/a.json#/definitions" => Some(("file:///bundle.zip".to_string(), "schemas/a.json".to_string())) ; "jar_url")]
+    #[test_case("zip+https://example.com/bundle.zip!/schemas/a%20b.json" => Some(("https://example.com/bundle.zip".to_string(), "schemas/a b.json".to_string())) ; "zip_url")]
+    #[test_case("tar+file:///bundle.tar.gz!/schemas/a.json" => Some(("file:///bundle.tar.gz".to_string(), "schemas/a.json".to_string())) ; "tar_url")]
+    #[test_case("file:///bundle.zip!/schemas/a.json" => None ; "not_archive_url")]
+    fn test_split_archive_url(url: &str) -> Option<(String, String)> {
+        split_archive_url(&Url::parse(url).unwrap())
+            .unwrap()
+            .map(|(archive_url, entry_path)| (archive_url.to_string(), entry_path))
+    }
+
+    #[test]
+    fn test_split_archive_url_without_entry_separator() {
+        assert!(split_archive_url(&Url::parse("jar:file:///bundle.zip").unwrap()).is_err());
+    }
+}
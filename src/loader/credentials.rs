@@ -0,0 +1,321 @@
+use crate::loader::error::LoaderError;
+use reqwest::blocking::RequestBuilder;
+use std::{
+    collections::HashMap,
+    fmt::{Debug, Error, Formatter},
+    path::{Path, PathBuf},
+};
+use url::Url;
+
+#[derive(Clone, PartialEq)]
+enum Authentication {
+    Basic { username: String, password: Option<String> },
+    Bearer(String),
+}
+
+impl Debug for Authentication {
+    // Secrets are not exposed, as loaders are frequently logged via their Debug representation
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            Self::Basic { username, .. } => f.debug_struct("Basic").field("username", username).finish(),
+            Self::Bearer(_) => f.write_str("Bearer"),
+        }
+    }
+}
+
+/// Origin (scheme, host and port) of the URLs credentials are registered for.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct Origin {
+    scheme: String,
+    host: String,
+    port: Option<u16>,
+}
+
+impl Origin {
+    fn of(url: &Url) -> Option<Self> {
+        url.host_str().map(|host| Self {
+            scheme: url.scheme().to_string(),
+            host: host.to_string(),
+            port: url.port_or_known_default(),
+        })
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+struct OriginCredentials {
+    headers: Vec<(String, String)>,
+    authentication: Option<Authentication>,
+}
+
+/// Headers and authentication sent alongside a request.
+type Credentials<'a> = (&'a [(String, String)], Option<&'a Authentication>);
+
+/// Provider of the headers and of the credentials to send alongside the requests of remote documents.
+///
+/// Credentials are registered per origin (scheme, host and port), the explicitly registered ones take precedence over the
+/// ones defined, per host, in a [netrc](https://everything.curl.dev/usingcurl/netrc) file.
+///
+/// Credentials are sent only over HTTPS, unless `with_plain_http` is used, and the redirects of requests carrying
+/// credentials are followed only within the origin of the requested URL (see [`RedirectPolicy`](crate::RedirectPolicy)).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CredentialsProvider {
+    origins: HashMap<Origin, OriginCredentials>,
+    netrc: Option<Netrc>,
+    plain_http: bool,
+}
+
+impl CredentialsProvider {
+    /// Send the `name: value` header to the origin of `url`.
+    #[must_use]
+    pub fn with_header<N: Into<String>, V: Into<String>>(mut self, url: &Url, name: N, value: V) -> Self {
+        if let Some(origin) = Origin::of(url) {
+            self.origins.entry(origin).or_default().headers.push((name.into(), value.into()));
+        }
+        self
+    }
+
+    /// Authenticate the requests to the origin of `url` via the `Authorization: Bearer <token>` header.
+    #[must_use]
+    pub fn with_bearer_token<S: Into<String>>(mut self, url: &Url, token: S) -> Self {
+        if let Some(origin) = Origin::of(url) {
+            self.origins.entry(origin).or_default().authentication = Some(Authentication::Bearer(token.into()));
+        }
+        self
+    }
+
+    /// Authenticate the requests to the origin of `url` via HTTP basic authentication.
+    #[must_use]
+    pub fn with_basic_auth<U: Into<String>, P: Into<String>>(mut self, url: &Url, username: U, password: Option<P>) -> Self {
+        if let Some(origin) = Origin::of(url) {
+            self.origins.entry(origin).or_default().authentication = Some(Authentication::Basic {
+                username: username.into(),
+                password: password.map(Into::into),
+            });
+        }
+        self
+    }
+
+    /// Send the credentials over plain HTTP too, where they could be read, or altered, by anyone on the network path.
+    #[must_use]
+    pub const fn with_plain_http(mut self) -> Self {
+        self.plain_http = true;
+        self
+    }
+
+    /// Authenticate the requests to the hosts without explicitly registered credentials via the netrc file at `path`.
+    pub fn with_netrc_file<P: AsRef<Path>>(mut self, path: P) -> Result<Self, LoaderError> {
+        self.netrc = Some(Netrc::parse(&std::fs::read_to_string(path)?)?);
+        Ok(self)
+    }
+
+    /// Authenticate the requests via the netrc file identified by the `NETRC` environment variable (or `~/.netrc` if not defined).
+    ///
+    /// A not existing netrc file is ignored.
+    pub fn with_default_netrc_file(self) -> Result<Self, LoaderError> {
+        match std::env::var_os("NETRC")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".netrc")))
+        {
+            Some(path) if path.is_file() => self.with_netrc_file(path),
+            _ => Ok(self),
+        }
+    }
+
+    /// Headers and authentication to send to `url`, if any.
+    fn credentials(&self, url: &Url) -> Option<Credentials<'_>> {
+        if url.scheme() != "https" && !self.plain_http {
+            return None;
+        }
+        let origin_credentials = Origin::of(url).and_then(|origin| self.origins.get(&origin));
+        let headers = origin_credentials.map_or(&[][..], |origin_credentials| &origin_credentials.headers);
+        let authentication = origin_credentials
+            .and_then(|origin_credentials| origin_credentials.authentication.as_ref())
+            .or_else(|| url.host_str().and_then(|host| self.netrc.as_ref().and_then(|netrc| netrc.get(host))));
+        if headers.is_empty() && authentication.is_none() {
+            None
+        } else {
+            Some((headers, authentication))
+        }
+    }
+
+    /// Whether the requests to `url` carry credentials, in which case their redirects should not leave the origin of `url`.
+    pub(in crate) fn has_credentials(&self, url: &Url) -> bool {
+        self.credentials(url).is_some()
+    }
+
+    /// Add the headers and the credentials registered for the origin of `url` to `request`.
+    pub fn authenticate(&self, url: &Url, request: RequestBuilder) -> RequestBuilder {
+        let (headers, authentication) = match self.credentials(url) {
+            Some(credentials) => credentials,
+            None => return request,
+        };
+        let request = headers.iter().fold(request, |request, (name, value)| request.header(name.as_str(), value.as_str()));
+        match authentication {
+            Some(Authentication::Basic { username, password }) => request.basic_auth(username, password.as_ref()),
+            Some(Authentication::Bearer(token)) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+}
+
+/// Credentials defined in a netrc file (only `login` and `password` tokens are considered).
+///
+/// Tokens could be double-quoted (ie. `password "with spaces"`), in which case `\"`, `\\`, `\n`, `\r` and `\t` are unescaped.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Netrc {
+    machines: HashMap<String, Authentication>,
+    default: Option<Authentication>,
+}
+
+impl Netrc {
+    fn parse(content: &str) -> Result<Self, LoaderError> {
+        let mut netrc = Self::default();
+        // Machine being defined (None for the default entry) and its login and password
+        let mut entry: Option<(Option<String>, Option<String>, Option<String>)> = None;
+
+        let mut tokens = tokenize(content)?.into_iter();
+        while let Some(token) = tokens.next() {
+            let mut next_value = || tokens.next().ok_or_else(|| LoaderError::FormatError(format!("Missing value of '{}' in netrc file", token)));
+            match token.as_str() {
+                "machine" | "default" => {
+                    netrc.add_entry(entry.take());
+                    entry = Some((if token == "machine" { Some(next_value()?) } else { None }, None, None));
+                }
+                "login" | "password" | "account" => {
+                    let value = next_value()?;
+                    if let Some((_, login, password)) = entry.as_mut() {
+                        match token.as_str() {
+                            "login" => *login = Some(value),
+                            "password" => *password = Some(value),
+                            _ => {}
+                        }
+                    }
+                }
+                "macdef" => return Err(LoaderError::FormatError("Macro definitions are not supported in netrc files".to_string())),
+                unknown_token => return Err(LoaderError::FormatError(format!("Unexpected token '{}' in netrc file", unknown_token))),
+            }
+        }
+        netrc.add_entry(entry);
+        Ok(netrc)
+    }
+
+    fn add_entry(&mut self, entry: Option<(Option<String>, Option<String>, Option<String>)>) {
+        if let Some((machine, Some(username), password)) = entry {
+            let authentication = Authentication::Basic { username, password };
+            match machine {
+                // The first matching entry is the one in use
+                Some(machine) => {
+                    let _d = self.machines.entry(machine).or_insert(authentication);
+                }
+                None => {
+                    let _d = self.default.get_or_insert(authentication);
+                }
+            }
+        }
+    }
+
+    fn get(&self, host: &str) -> Option<&Authentication> {
+        self.machines.get(host).or(self.default.as_ref())
+    }
+}
+
+/// Split the netrc file `content` into its (whitespace separated, and possibly double-quoted) tokens, ignoring the comment lines.
+fn tokenize(content: &str) -> Result<Vec<String>, LoaderError> {
+    let mut tokens = Vec::new();
+    for line in content.lines().filter(|line| !line.trim_start().starts_with('#')) {
+        let mut characters = line.chars().peekable();
+        while let Some(character) = characters.next() {
+            if character.is_whitespace() {
+                continue;
+            }
+            let mut token = String::new();
+            if character == '"' {
+                loop {
+                    match characters.next() {
+                        Some('"') => break,
+                        Some('\\') => match characters.next() {
+                            Some('n') => token.push('\n'),
+                            Some('r') => token.push('\r'),
+                            Some('t') => token.push('\t'),
+                            Some(escaped_character) => token.push(escaped_character),
+                            None => return Err(LoaderError::FormatError("Unterminated quoted token in netrc file".to_string())),
+                        },
+                        Some(quoted_character) => token.push(quoted_character),
+                        None => return Err(LoaderError::FormatError("Unterminated quoted token in netrc file".to_string())),
+                    }
+                }
+            } else {
+                token.push(character);
+                while let Some(token_character) = characters.next_if(|next_character| !next_character.is_whitespace()) {
+                    token.push(token_character);
+                }
+            }
+            tokens.push(token);
+        }
+    }
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Authentication, CredentialsProvider, Netrc};
+    use crate::loader::error::LoaderError;
+    use test_case::test_case;
+    use url::Url;
+
+    fn basic(username: &str, password: Option<&str>) -> Authentication {
+        Authentication::Basic {
+            username: username.to_string(),
+            password: password.map(ToString::to_string),
+        }
+    }
+
+    #[test]
+    fn test_parse_netrc() {
+        let netrc = Netrc::parse(
+            "# Comment\n\
+             machine example.com login user password secret\n\
+             machine example.com login ignored password ignored\n\
+             machine other.example.com\n  account unused\n  login other-user\n\
+             default login anonymous password guest\n",
+        )
+        .unwrap();
+
+        assert_eq!(netrc.get("example.com"), Some(&basic("user", Some("secret"))));
+        assert_eq!(netrc.get("other.example.com"), Some(&basic("other-user", None)));
+        assert_eq!(netrc.get("unknown.example.com"), Some(&basic("anonymous", Some("guest"))));
+    }
+
+    #[test]
+    fn test_parse_netrc_quoted_tokens() {
+        let netrc = Netrc::parse("machine example.com login \"user name\" password \"a \\\"secret\\\" \\\\ with # spaces\"\n").unwrap();
+        assert_eq!(netrc.get("example.com"), Some(&basic("user name", Some("a \"secret\" \\ with # spaces"))));
+    }
+
+    #[test_case("machine example.com login", "Missing value of 'login' in netrc file" ; "missing_value")]
+    #[test_case("machine example.com login \"user", "Unterminated quoted token in netrc file" ; "unterminated_quoted_token")]
+    fn test_parse_invalid_netrc(content: &str, expected_message: &str) {
+        assert!(matches!(
+            Netrc::parse(content).unwrap_err(),
+            LoaderError::FormatError(message) if message == expected_message
+        ));
+    }
+
+    #[test_case("https://example.com/document.json" => true ; "same_origin")]
+    #[test_case("https://example.com:443/other/document.json" => true ; "same_origin_with_default_port")]
+    #[test_case("https://example.com:8443/document.json" => false ; "other_port")]
+    #[test_case("https://other.example.com/document.json" => false ; "other_host")]
+    #[test_case("http://example.com/document.json" => false ; "other_scheme")]
+    fn test_has_credentials(url: &str) -> bool {
+        CredentialsProvider::default()
+            .with_header(&Url::parse("https://example.com").unwrap(), "x-api-key", "key")
+            .has_credentials(&Url::parse(url).unwrap())
+    }
+
+    #[test_case(CredentialsProvider::default() => false ; "https_only")]
+    #[test_case(CredentialsProvider::default().with_plain_http() => true ; "plain_http")]
+    fn test_has_credentials_over_plain_http(credentials_provider: CredentialsProvider) -> bool {
+        let url = Url::parse("http://example.com/document.json").unwrap();
+        credentials_provider.with_bearer_token(&url, "token").has_credentials(&url)
+    }
+}
@@ -0,0 +1,182 @@
+use crate::loader::error::LoaderError;
+use std::path::Path;
+#[cfg(any(feature = "decompression-gzip", feature = "decompression-zstd", feature = "decompression-brotli", feature = "archive-zip"))]
+use std::{convert::TryFrom, io::Read};
+use url::Url;
+
+/// Maximum size, in bytes, of the (decompressed) content of the documents and of the archive entries, unless configured
+/// otherwise (see `Loader::with_max_content_size`).
+pub(in crate) const DEFAULT_MAX_CONTENT_SIZE: usize = 64 * 1024 * 1024;
+
+const GZIP_MAGIC_BYTES: [u8; 2] = [0x1f, 0x8b];
+const ZSTD_MAGIC_BYTES: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+#[cfg(feature = "decompression-brotli")]
+const BROTLI_BUFFER_SIZE: usize = 4096;
+
+/// Compression formats of the documents that are transparently decompressed, if the respective `decompression-*` feature is enabled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Compression {
+    Gzip,
+    Zstd,
+    Brotli,
+}
+
+impl Compression {
+    fn from_content_encoding(content_encoding: &str) -> Option<Self> {
+        match content_encoding.trim().to_ascii_lowercase().as_str() {
+            "gzip" | "x-gzip" => Some(Self::Gzip),
+            "zstd" => Some(Self::Zstd),
+            "br" => Some(Self::Brotli),
+            _ => None,
+        }
+    }
+
+    // Brotli streams have no magic bytes, so they are recognized by the extension of the URL only
+    fn detect(url: &Url, content: &[u8]) -> Option<Self> {
+        if content.starts_with(&GZIP_MAGIC_BYTES) {
+            Some(Self::Gzip)
+        } else if content.starts_with(&ZSTD_MAGIC_BYTES) {
+            Some(Self::Zstd)
+        } else if Path::new(url.path()).extension().map_or(false, |extension| extension.eq_ignore_ascii_case("br")) {
+            Some(Self::Brotli)
+        } else {
+            None
+        }
+    }
+
+    const fn is_enabled(self) -> bool {
+        match self {
+            Self::Gzip => cfg!(feature = "decompression-gzip"),
+            Self::Zstd => cfg!(feature = "decompression-zstd"),
+            Self::Brotli => cfg!(feature = "decompression-brotli"),
+        }
+    }
+
+    // The decompressed content is `None` if larger than `max_size`
+    #[cfg_attr(
+        not(all(feature = "decompression-gzip", feature = "decompression-zstd", feature = "decompression-brotli")),
+        allow(unused_variables)
+    )]
+    fn decompress(self, content: &[u8], max_size: usize) -> Result<Option<Vec<u8>>, std::io::Error> {
+        match self {
+            #[cfg(feature = "decompression-gzip")]
+            Self::Gzip => read_to_end_with_limit(flate2::read::MultiGzDecoder::new(content), max_size),
+            #[cfg(feature = "decompression-zstd")]
+            Self::Zstd => read_to_end_with_limit(zstd::stream::read::Decoder::with_buffer(content)?, max_size),
+            #[cfg(feature = "decompression-brotli")]
+            Self::Brotli => read_to_end_with_limit(brotli_decompressor::Decompressor::new(content, BROTLI_BUFFER_SIZE), max_size),
+            #[allow(unreachable_patterns)]
+            _ => unreachable!("Compression {:?} is not enabled", self),
+        }
+    }
+}
+
+/// Read `reader` to its end, `None` is returned (without reading further) if it yields more than `max_size` bytes.
+#[cfg(any(feature = "decompression-gzip", feature = "decompression-zstd", feature = "decompression-brotli", feature = "archive-zip"))]
+pub(in crate) fn read_to_end_with_limit<R: Read>(reader: R, max_size: usize) -> Result<Option<Vec<u8>>, std::io::Error> {
+    let mut content = Vec::new();
+    let _d = reader.take(u64::try_from(max_size).unwrap_or(u64::MAX).saturating_add(1)).read_to_end(&mut content)?;
+    Ok(if content.len() > max_size { None } else { Some(content) })
+}
+
+/// Error reporting that the content identified by `url` is larger than `max_size` bytes.
+pub(in crate) fn content_too_large_error(url: &Url, max_size: usize) -> LoaderError {
+    LoaderError::FormatError(format!("Content of {} exceeds the maximum size of {} bytes", url, max_size))
+}
+
+fn check_content_size(url: &Url, content: Vec<u8>, max_size: usize) -> Result<Vec<u8>, LoaderError> {
+    if content.len() > max_size {
+        Err(content_too_large_error(url, max_size))
+    } else {
+        Ok(content)
+    }
+}
+
+/// Value of the `Accept-Encoding` header advertising the compression formats supported by the loaders.
+///
+/// gzip is always advertised, as it is decompressed by the HTTP client itself.
+pub(in crate) fn accept_encoding() -> String {
+    let mut encodings = vec!["gzip"];
+    if Compression::Zstd.is_enabled() {
+        encodings.push("zstd");
+    }
+    if Compression::Brotli.is_enabled() {
+        encodings.push("br");
+    }
+    encodings.join(", ")
+}
+
+/// Decompress `content`, retrieved from `url` with the `content_encoding` `Content-Encoding` header (if any), failing if
+/// the decompressed content is larger than `max_size` bytes.
+///
+/// Without `Content-Encoding` the compression format is detected via the magic bytes of `content` (gzip and zstd)
+/// or via the extension of `url` (brotli), and formats whose feature is not enabled are returned as they are.
+pub(in crate) fn decompress(url: &Url, content_encoding: Option<&str>, content: Vec<u8>, max_size: usize) -> Result<Vec<u8>, LoaderError> {
+    let compression = match content_encoding.and_then(Compression::from_content_encoding) {
+        Some(compression) => compression,
+        None => match Compression::detect(url, &content) {
+            Some(compression) => compression,
+            None => return check_content_size(url, content, max_size),
+        },
+    };
+    if !compression.is_enabled() {
+        return check_content_size(url, content, max_size);
+    }
+    compression
+        .decompress(&content, max_size)
+        .map_err(|io_error| LoaderError::FormatError(format!("Invalid {:?} compressed content of {}: {}", compression, url, io_error)))?
+        .ok_or_else(|| content_too_large_error(url, max_size))
+}
+
+#[cfg(all(test, feature = "decompression-gzip", feature = "decompression-zstd", feature = "decompression-brotli"))]
+mod tests {
+    use super::{accept_encoding, decompress, DEFAULT_MAX_CONTENT_SIZE};
+    use crate::{loader::error::LoaderError, testing_helpers::test_data_file_path};
+    use test_case::test_case;
+    use url::Url;
+
+    #[test_case("Object.json.gz", "https://example.com/Object.json.gz", None ; "gzip_magic_bytes")]
+    #[test_case("Object.json.zst", "https://example.com/Object.json", None ; "zstd_magic_bytes")]
+    #[test_case("Object.json.br", "https://example.com/Object.json.br", None ; "brotli_extension")]
+    #[test_case("Object.json.br", "https://example.com/Object.json", Some("br") ; "brotli_content_encoding")]
+    #[test_case("Object.json", "https://example.com/Object.json", None ; "not_compressed")]
+    fn test_decompress(file_name: &str, url: &str, content_encoding: Option<&str>) {
+        assert_eq!(
+            decompress(
+                &Url::parse(url).unwrap(),
+                content_encoding,
+                std::fs::read(test_data_file_path(&[file_name]).unwrap()).unwrap(),
+                DEFAULT_MAX_CONTENT_SIZE,
+            )
+            .unwrap(),
+            std::fs::read(test_data_file_path(&["Object.json"]).unwrap()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_decompress_invalid_content() {
+        let url = Url::parse("file:///Invalid.json.br").unwrap();
+        assert!(matches!(
+            decompress(&url, None, b"not brotli".to_vec(), DEFAULT_MAX_CONTENT_SIZE).unwrap_err(),
+            LoaderError::FormatError(message) if message.starts_with("Invalid Brotli compressed content of file:///Invalid.json.br")
+        ));
+    }
+
+    #[test_case("Object.json.gz", "https://example.com/Object.json.gz" ; "gzip")]
+    #[test_case("Object.json.zst", "https://example.com/Object.json" ; "zstd")]
+    #[test_case("Object.json.br", "https://example.com/Object.json.br" ; "brotli")]
+    #[test_case("Object.json", "https://example.com/Object.json" ; "not_compressed")]
+    fn test_decompress_exceeding_max_size(file_name: &str, url: &str) {
+        let max_size = std::fs::read(test_data_file_path(&["Object.json"]).unwrap()).unwrap().len() - 1;
+        let content = std::fs::read(test_data_file_path(&[file_name]).unwrap()).unwrap();
+        assert!(matches!(
+            decompress(&Url::parse(url).unwrap(), None, content, max_size).unwrap_err(),
+            LoaderError::FormatError(message) if message == format!("Content of {} exceeds the maximum size of {} bytes", url, max_size)
+        ));
+    }
+
+    #[test]
+    fn test_accept_encoding() {
+        assert_eq!(accept_encoding(), "gzip, zstd, br");
+    }
+}
@@ -0,0 +1,93 @@
+use parking_lot::Mutex;
+use std::collections::{HashMap, HashSet};
+use url::Url;
+
+#[derive(Debug, Default)]
+struct DependencyGraph {
+    // Documents each document depends on (ie. it includes)
+    dependencies: HashMap<Url, HashSet<Url>>,
+    // Documents depending on each document
+    dependents: HashMap<Url, HashSet<Url>>,
+}
+
+/// Dependencies between cached documents, identified by their (fragmentless) cache keys, such that
+/// the documents built on top of others (ie. archive entries) are invalidated alongside them.
+#[derive(Debug, Default)]
+pub struct DocumentDependencies(Mutex<DependencyGraph>);
+
+impl DocumentDependencies {
+    /// Record that `document` is built on top of `dependency`.
+    pub(in crate) fn add(&self, document: &Url, dependency: &Url) {
+        let mut graph = self.0.lock();
+        let _d = graph.dependencies.entry(document.clone()).or_default().insert(dependency.clone());
+        let _d = graph.dependents.entry(dependency.clone()).or_default().insert(document.clone());
+    }
+
+    /// Documents `document` is built on top of.
+    pub(in crate) fn dependencies(&self, document: &Url) -> Vec<Url> {
+        self.0
+            .lock()
+            .dependencies
+            .get(document)
+            .map_or_else(Vec::new, |dependencies| dependencies.iter().cloned().collect())
+    }
+
+    /// Forget the dependencies of `document`, which are recorded again once it is loaded again, and
+    /// return the documents depending on it (which no longer depend on it, as they are expected to be invalidated too).
+    pub(in crate) fn remove(&self, document: &Url) -> Vec<Url> {
+        let mut graph = self.0.lock();
+        for dependency in graph.dependencies.remove(document).unwrap_or_default() {
+            if let Some(dependents) = graph.dependents.get_mut(&dependency) {
+                let _d = dependents.remove(document);
+            }
+        }
+        let dependents: Vec<Url> = graph.dependents.remove(document).map_or_else(Vec::new, |dependents| dependents.into_iter().collect());
+        for dependent in &dependents {
+            if let Some(dependencies) = graph.dependencies.get_mut(dependent) {
+                let _d = dependencies.remove(document);
+                if dependencies.is_empty() {
+                    let _d = graph.dependencies.remove(dependent);
+                }
+            }
+        }
+        dependents
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DocumentDependencies;
+    use url::Url;
+
+    fn url(path: &str) -> Url {
+        Url::parse("memory:///").unwrap().join(path).unwrap()
+    }
+
+    #[test]
+    fn test_remove_returns_dependents() {
+        let dependencies = DocumentDependencies::default();
+        dependencies.add(&url("parent"), &url("child"));
+        dependencies.add(&url("other"), &url("child"));
+        dependencies.add(&url("child"), &url("grandchild"));
+
+        let mut dependents = dependencies.remove(&url("child"));
+        dependents.sort();
+        assert_eq!(dependents, vec![url("other"), url("parent")]);
+        assert!(dependencies.remove(&url("child")).is_empty());
+        assert!(dependencies.dependencies(&url("parent")).is_empty());
+        // The dependencies of the removed document are recorded again once it is loaded
+        assert!(dependencies.dependencies(&url("child")).is_empty());
+        assert!(dependencies.remove(&url("grandchild")).is_empty());
+    }
+
+    #[test]
+    fn test_remove_forgets_dependencies_of_removed_document() {
+        let dependencies = DocumentDependencies::default();
+        dependencies.add(&url("parent"), &url("child"));
+        assert_eq!(dependencies.dependencies(&url("parent")), vec![url("child")]);
+
+        assert!(dependencies.remove(&url("parent")).is_empty());
+        assert!(dependencies.dependencies(&url("parent")).is_empty());
+        assert!(dependencies.remove(&url("child")).is_empty());
+    }
+}
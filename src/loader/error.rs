@@ -9,6 +9,8 @@ pub enum LoaderError {
     // We're not saving the real error instance, but only it's Display representation
     // in order to simplify the interface of the LoaderTrait trait
     FormatError(String),
+    // Failure reported by a validation hook
+    ValidationError(String),
     UnknownError,
 }
 
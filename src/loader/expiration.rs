@@ -0,0 +1,73 @@
+use std::time::{Duration, Instant};
+
+/// Freshness of a cached document according to an [`ExpirationPolicy`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(in crate) enum Freshness {
+    Fresh,
+    // Expired, but still returned while being refreshed in background
+    Stale,
+    Expired,
+}
+
+/// Policy expiring the cached documents, such that they are fetched again once older than the time to live.
+///
+/// With stale-while-revalidate, expired documents are still returned, for up to the maximum staleness, while being
+/// refreshed in background (via a conditional request if the documents were retrieved alongside the `ETag` or
+/// `Last-Modified` response headers). The refreshed documents replace the cached ones on the following lookup.
+/// Documents not retrieved via HTTP (ie. `file://` URLs) are always fetched again once expired.
+///
+/// The time to live applies to all the documents alike: the `Cache-Control` and `Expires` response headers, though
+/// recorded by [`FetchMetadata`](crate::loader::metadata::FetchMetadata), are not taken into account. The age of the
+/// documents is tracked by their fetch metadata, so the documents cached without it (ie. via `LoaderTrait::save_in_cache`)
+/// never expire. Archives expire as the other documents, alongside the entries read from them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ExpirationPolicy {
+    ttl: Duration,
+    max_staleness: Option<Duration>,
+}
+
+impl ExpirationPolicy {
+    /// Expire the cached documents `ttl` after their retrieval.
+    #[must_use]
+    pub const fn new(ttl: Duration) -> Self {
+        Self { ttl, max_staleness: None }
+    }
+
+    /// Return the expired documents, for up to `max_staleness` after their expiration, while refreshing them in background.
+    #[must_use]
+    pub const fn with_stale_while_revalidate(mut self, max_staleness: Duration) -> Self {
+        self.max_staleness = Some(max_staleness);
+        self
+    }
+
+    /// Freshness of a document retrieved `age` ago.
+    pub(in crate) fn freshness(&self, age: Duration) -> Freshness {
+        if age <= self.ttl {
+            Freshness::Fresh
+        } else if self.max_staleness.map_or(false, |max_staleness| age.saturating_sub(self.ttl) <= max_staleness) {
+            Freshness::Stale
+        } else {
+            Freshness::Expired
+        }
+    }
+
+    /// Freshness of a document retrieved at `fetched_at`.
+    pub(in crate) fn freshness_since(&self, fetched_at: Instant) -> Freshness {
+        self.freshness(fetched_at.elapsed())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ExpirationPolicy, Freshness};
+    use std::time::Duration;
+    use test_case::test_case;
+
+    #[test_case(ExpirationPolicy::new(Duration::from_secs(10)), 5 => Freshness::Fresh ; "fresh")]
+    #[test_case(ExpirationPolicy::new(Duration::from_secs(10)), 15 => Freshness::Expired ; "expired")]
+    #[test_case(ExpirationPolicy::new(Duration::from_secs(10)).with_stale_while_revalidate(Duration::from_secs(10)), 15 => Freshness::Stale ; "stale")]
+    #[test_case(ExpirationPolicy::new(Duration::from_secs(10)).with_stale_while_revalidate(Duration::from_secs(10)), 25 => Freshness::Expired ; "expired_beyond_max_staleness")]
+    fn test_freshness(expiration_policy: ExpirationPolicy, age_seconds: u64) -> Freshness {
+        expiration_policy.freshness(Duration::from_secs(age_seconds))
+    }
+}
@@ -0,0 +1,68 @@
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use url::Url;
+
+/// Response headers recorded into [`FetchMetadata::headers`], the other ones are discarded.
+pub(in crate) const METADATA_HEADERS: [&str; 5] = ["cache-control", "content-encoding", "etag", "expires", "last-modified"];
+
+/// Metadata of the retrieval of a document.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct FetchMetadata {
+    /// URL the document was finally retrieved from (after following redirects), without fragment.
+    pub url: Url,
+    /// Status of the HTTP response (`None` for `file://` URLs).
+    pub status: Option<u16>,
+    /// Value of the `Content-Type` response header.
+    pub content_type: Option<String>,
+    /// Values of the response headers listed by `METADATA_HEADERS` (`cache-control`, `content-encoding`, `etag`,
+    /// `expires` and `last-modified`), with lower case names.
+    pub headers: Vec<(String, String)>,
+    /// Size, in bytes, of the retrieved (and eventually decompressed) content.
+    pub size: usize,
+    /// Time spent retrieving the content.
+    pub duration: Duration,
+    /// Instant the content was retrieved (or last revalidated) at, which cached documents expire from (see `ExpirationPolicy`).
+    pub fetched_at: Instant,
+}
+
+impl FetchMetadata {
+    /// Metadata of content retrieved without HTTP requests (ie. from the local file system).
+    pub(in crate) fn without_response(url: Url, size: usize, duration: Duration) -> Self {
+        Self {
+            url,
+            status: None,
+            content_type: None,
+            headers: Vec::new(),
+            size,
+            duration,
+            fetched_at: Instant::now(),
+        }
+    }
+
+    /// Value of the recorded response header `name` (case insensitive, see `headers`).
+    #[must_use]
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header_name, _)| header_name.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Loaded value, alongside the metadata of the retrieval of the document it comes from.
+#[derive(Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Loaded<T> {
+    pub value: Arc<T>,
+    /// Requested URL after following redirects (its fragment is preserved).
+    pub url: Url,
+    /// Metadata of the retrieval of the document, `None` if the document was cached without being fetched by the loader
+    /// (ie. via `save_in_cache`).
+    pub metadata: Option<Arc<FetchMetadata>>,
+    /// Whether the value was retrieved from the caches (see `LoaderTrait::get_or_fetch_with_cache_status`), in which case
+    /// `metadata` describes the original retrieval of its document.
+    pub from_cache: bool,
+}
@@ -0,0 +1,125 @@
+use crate::{
+    loader::{
+        credentials::CredentialsProvider,
+        metadata::FetchMetadata,
+        timeout::Timeouts,
+        trait_::{fetch_remote, FetchedContent},
+    },
+    thread_safe_cache::ThreadSafeCacheTrait,
+};
+use reqwest::blocking::Client;
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+use url::Url;
+
+// Delay before retrying a failed refresh, doubled on every consecutive failure up to MAX_RETRY_DELAY
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+// Refreshes running at the same time (across all the loaders), the stale documents are refreshed later beyond it
+const MAX_ACTIVE_REFRESHES: usize = 8;
+
+static ACTIVE_REFRESHES: AtomicUsize = AtomicUsize::new(0);
+
+/// Background refresh of a stale document (see `ExpirationPolicy::with_stale_while_revalidate`).
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug)]
+pub enum Refresh {
+    /// The document is being refreshed.
+    Pending,
+    /// Modified content, used by the next fetch of the document.
+    Modified(FetchedContent),
+    /// The cached document is still up to date.
+    NotModified,
+    /// The last `failures` refreshes failed, the document is refreshed again once `retry_at` is passed.
+    Failed { retry_at: Instant, failures: u32 },
+}
+
+impl Refresh {
+    /// Whether a new refresh of the document should be started, as none is pending or waiting to be retried.
+    pub(in crate) fn is_due(refresh: Option<&Self>) -> bool {
+        match refresh {
+            None => true,
+            Some(Self::Failed { retry_at, .. }) => Instant::now() >= *retry_at,
+            Some(_) => false,
+        }
+    }
+
+    fn failed(previous_failures: u32) -> Self {
+        let retry_delay = RETRY_DELAY
+            .checked_mul(2_u32.saturating_pow(previous_failures))
+            .map_or(MAX_RETRY_DELAY, |retry_delay| retry_delay.min(MAX_RETRY_DELAY));
+        Self::Failed {
+            retry_at: Instant::now() + retry_delay,
+            failures: previous_failures.saturating_add(1),
+        }
+    }
+}
+
+// Slot among the MAX_ACTIVE_REFRESHES, released once dropped
+struct ActiveRefresh;
+
+impl ActiveRefresh {
+    fn acquire() -> Option<Self> {
+        ACTIVE_REFRESHES
+            .fetch_update(
+                Ordering::SeqCst,
+                Ordering::SeqCst,
+                |active| if active < MAX_ACTIVE_REFRESHES { Some(active + 1) } else { None },
+            )
+            .ok()
+            .map(|_| Self)
+    }
+}
+
+impl Drop for ActiveRefresh {
+    fn drop(&mut self) {
+        let _d = ACTIVE_REFRESHES.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Refresh in background the document at `url`, whose previous retrieval is described by `metadata`.
+///
+/// Failed refreshes are retried, on the lookups of the (still stale) document, after a delay growing with the consecutive
+/// failures. No refresh is started while too many are running, so it is started by a later lookup instead.
+pub(in crate) fn refresh_in_background(
+    refreshes_cache: &Arc<dyn ThreadSafeCacheTrait<Url, Refresh> + Send + Sync>,
+    client: Client,
+    credentials_provider: Option<CredentialsProvider>,
+    url: &Url,
+    timeouts: Timeouts,
+    max_content_size: usize,
+    metadata: Arc<FetchMetadata>,
+) {
+    let active_refresh = match ActiveRefresh::acquire() {
+        Some(active_refresh) => active_refresh,
+        None => return,
+    };
+    let previous_failures = match refreshes_cache.get(url).as_deref() {
+        Some(Refresh::Failed { failures, .. }) => *failures,
+        _ => 0,
+    };
+    let pending = Arc::new(Refresh::Pending);
+    refreshes_cache.set(url, pending.clone());
+    let thread_refreshes_cache = refreshes_cache.clone();
+    let thread_url = url.clone();
+    let thread_pending = pending.clone();
+    let spawn_result = thread::Builder::new().name("loader-rs-refresh".to_string()).spawn(move || {
+        let _active_refresh = active_refresh;
+        let refresh = match fetch_remote(&client, credentials_provider.as_ref(), &thread_url, &timeouts, max_content_size, Some(&metadata)) {
+            Ok(Some(fetched_content)) => Refresh::Modified(fetched_content),
+            Ok(None) => Refresh::NotModified,
+            Err(_) => Refresh::failed(previous_failures),
+        };
+        // The refresh is discarded if the document was fetched again (or another refresh started) in the meantime
+        let _d = thread_refreshes_cache.compare_and_set(&thread_url, &thread_pending, Arc::new(refresh));
+    });
+    if spawn_result.is_err() {
+        let _d = refreshes_cache.compare_and_set(url, &pending, Arc::new(Refresh::failed(previous_failures)));
+    }
+}
@@ -0,0 +1,128 @@
+use crate::{
+    loader::{error::LoaderError, metadata::FetchMetadata, trait_::LoaderTrait},
+    url_helpers::remove_fragment_from_url,
+};
+use std::{
+    fmt::{Debug, Error, Formatter},
+    sync::{
+        mpsc::{channel, sync_channel, Receiver, RecvTimeoutError, Sender, TrySendError},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+use url::Url;
+
+/// Handle of a subscription to the changes of a value, created via `LoaderTrait::subscribe`.
+///
+/// Every `poll_interval` the value is looked up again, after revalidating the remote document via a conditional request
+/// (see `LoaderTrait::revalidate`) and invalidating the changed files (see the `file-watcher` feature). Updated values,
+/// and the errors occurred while looking them up, are delivered through the channel of the subscription.
+///
+/// An error is delivered only if it differs from the previous one, and the value is delivered again once looked up
+/// successfully after an error. The channel holds a single update, so the updates happened meanwhile it is not received
+/// are coalesced into the latest one.
+///
+/// Dropping the handle terminates the subscription.
+pub struct Subscription<T> {
+    url: Url,
+    updates: Receiver<Result<Arc<T>, LoaderError>>,
+    // The polling thread terminates once the sender is dropped
+    _stop_sender: Sender<()>,
+}
+
+impl<T> Debug for Subscription<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        f.debug_struct("Subscription").field("url", &self.url).finish_non_exhaustive()
+    }
+}
+
+impl<T: Send + Sync + 'static> Subscription<T> {
+    /// Subscribe to the changes of the value identified by `key`, whose current value is loaded right away such that
+    /// failures are reported to the caller.
+    pub(in crate) fn new<L: LoaderTrait<T> + Send + Sync + 'static>(loader: Arc<L>, key: &Url, poll_interval: Duration) -> Result<Self, LoaderError> {
+        let value = loader.get_or_fetch_with_result(key)?;
+        let (update_sender, updates) = sync_channel(1);
+        let (stop_sender, stop_receiver) = channel::<()>();
+
+        let thread_key = key.clone();
+        let _d = thread::Builder::new().name("loader-rs-subscription".to_string()).spawn(move || {
+            let mut version = Version::of(&*loader, &thread_key, value);
+            let mut last_error_message: Option<String> = None;
+            let mut pending_update = None;
+            while stop_receiver.recv_timeout(poll_interval) == Err(RecvTimeoutError::Timeout) {
+                match loader.revalidate(&thread_key).and_then(|_| loader.get_or_fetch_with_result(&thread_key)) {
+                    Ok(value) => {
+                        let current_version = Version::of(&*loader, &thread_key, value);
+                        let recovered = last_error_message.take().is_some();
+                        if recovered || !current_version.is_same(&version) {
+                            version = current_version;
+                            pending_update = Some(Ok(version.value.clone()));
+                        }
+                    }
+                    Err(error) => {
+                        let error_message = error.to_string();
+                        if last_error_message.as_ref() != Some(&error_message) {
+                            last_error_message = Some(error_message);
+                            pending_update = Some(Err(error));
+                        }
+                    }
+                }
+                // The pending update is replaced by the later ones until the channel has room for it
+                if let Some(update) = pending_update.take() {
+                    match update_sender.try_send(update) {
+                        Ok(()) => {}
+                        Err(TrySendError::Full(update)) => pending_update = Some(update),
+                        Err(TrySendError::Disconnected(_)) => break,
+                    }
+                }
+            }
+        })?;
+
+        Ok(Self {
+            url: key.clone(),
+            updates,
+            _stop_sender: stop_sender,
+        })
+    }
+}
+
+impl<T> Subscription<T> {
+    /// URL of the subscribed value.
+    pub const fn url(&self) -> &Url {
+        &self.url
+    }
+
+    /// Channel delivering the updated values, and the errors occurred while looking them up.
+    pub const fn updates(&self) -> &Receiver<Result<Arc<T>, LoaderError>> {
+        &self.updates
+    }
+}
+
+// Value looked up by the subscription, alongside the metadata of the retrieval of its document
+struct Version<T> {
+    value: Arc<T>,
+    metadata: Option<Arc<FetchMetadata>>,
+}
+
+impl<T> Version<T> {
+    fn of<L: LoaderTrait<T>>(loader: &L, key: &Url, value: Arc<T>) -> Self {
+        Self {
+            value,
+            metadata: loader
+                .get_fetch_metadata_cache()
+                .and_then(|fetch_metadata_cache| fetch_metadata_cache.get(&remove_fragment_from_url(key))),
+        }
+    }
+
+    // Documents are fetched again only if invalidated, so the metadata recorded by every fetch identifies their versions
+    // (revalidated documents could be recorded again alongside the same value). Values extracted from fragments could be
+    // extracted again on every lookup, so the values themselves are not enough.
+    fn is_same(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.value, &other.value)
+            || match (&self.metadata, &other.metadata) {
+                (Some(metadata), Some(other_metadata)) => Arc::ptr_eq(metadata, other_metadata),
+                _ => false,
+            }
+    }
+}
@@ -0,0 +1,92 @@
+use crate::loader::trait_::DEFAULT_TIMEOUT;
+use std::{collections::HashMap, time::Duration};
+use url::Url;
+
+/// Timeouts of the requests of remote documents.
+///
+/// The connect timeout bounds the establishment of the connection, the read timeout bounds every single read
+/// of the response and the total timeout bounds the whole request, up to the end of the response body.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Timeouts {
+    pub connect: Option<Duration>,
+    pub read: Option<Duration>,
+    pub total: Duration,
+}
+
+impl Default for Timeouts {
+    fn default() -> Self {
+        Self {
+            connect: None,
+            read: None,
+            total: DEFAULT_TIMEOUT,
+        }
+    }
+}
+
+impl Timeouts {
+    #[must_use]
+    pub const fn with_connect(mut self, connect: Duration) -> Self {
+        self.connect = Some(connect);
+        self
+    }
+
+    #[must_use]
+    pub const fn with_read(mut self, read: Duration) -> Self {
+        self.read = Some(read);
+        self
+    }
+
+    #[must_use]
+    pub const fn with_total(mut self, total: Duration) -> Self {
+        self.total = total;
+        self
+    }
+}
+
+/// Timeouts of the requests, with overrides per host (as returned by `Url::host_str`).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TimeoutPolicy {
+    default: Timeouts,
+    hosts: HashMap<String, Timeouts>,
+}
+
+impl TimeoutPolicy {
+    /// Use `timeouts` for the hosts without overrides.
+    #[must_use]
+    pub fn with_default(mut self, timeouts: Timeouts) -> Self {
+        self.default = timeouts;
+        self
+    }
+
+    /// Use `timeouts` for the requests to `host`.
+    #[must_use]
+    pub fn with_host<H: Into<String>>(mut self, host: H, timeouts: Timeouts) -> Self {
+        let _d = self.hosts.insert(host.into(), timeouts);
+        self
+    }
+
+    /// Timeouts of the requests of `url`.
+    #[must_use]
+    pub fn timeouts(&self, url: &Url) -> Timeouts {
+        url.host_str().and_then(|host| self.hosts.get(host)).copied().unwrap_or(self.default)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TimeoutPolicy, Timeouts};
+    use std::time::Duration;
+    use test_case::test_case;
+    use url::Url;
+
+    #[test_case("https://example.com/schema.json" => Duration::from_secs(5) ; "overridden_host")]
+    #[test_case("https://www.example.com/schema.json" => Duration::from_secs(1) ; "other_host")]
+    #[test_case("file:///schema.json" => Duration::from_secs(1) ; "no_host")]
+    fn test_timeouts(url: &str) -> Duration {
+        TimeoutPolicy::default()
+            .with_default(Timeouts::default().with_total(Duration::from_secs(1)))
+            .with_host("example.com", Timeouts::default().with_total(Duration::from_secs(5)))
+            .timeouts(&Url::parse(url).unwrap())
+            .total
+    }
+}
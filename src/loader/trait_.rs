@@ -1,6 +1,28 @@
-use crate::loader::error::LoaderError;
-use reqwest::blocking::Client;
-use std::{fmt::Debug, sync::Arc, time::Duration};
+#[cfg(feature = "file-watcher")]
+use crate::loader::watcher::FileWatcher;
+use crate::loader::{
+    archive::{split_archive_url, Archive},
+    credentials::CredentialsProvider,
+    decompression::{accept_encoding, content_too_large_error, decompress, DEFAULT_MAX_CONTENT_SIZE},
+    dependencies::DocumentDependencies,
+    error::LoaderError,
+    expiration::{ExpirationPolicy, Freshness},
+    metadata::{FetchMetadata, Loaded, METADATA_HEADERS},
+    refresh::{refresh_in_background, Refresh},
+    subscription::Subscription,
+    timeout::Timeouts,
+};
+use reqwest::{
+    blocking::Client,
+    header::{ACCEPT_ENCODING, CONTENT_ENCODING, CONTENT_TYPE, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED},
+    StatusCode,
+};
+use std::{
+    fmt::Debug,
+    io::Read,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 use url::Url;
 
 use crate::{
@@ -8,14 +30,107 @@ use crate::{
     url_helpers::{parse_and_normalize_url, remove_fragment_from_url},
 };
 
+pub(in crate) const DEFAULT_TIMEOUT: Duration = Duration::from_millis(30_000);
+
 pub trait GetCache<T> {
     fn get_cache(&self) -> &dyn ThreadSafeCacheTrait<Url, T>;
+
+    /// Optional cache of the values extracted from URL fragments, keyed by the full URL.
+    fn get_fragment_cache(&self) -> Option<&dyn ThreadSafeCacheTrait<Url, T>> {
+        None
+    }
+
+    /// Optional cache of the metadata of the retrieval of the documents, keyed by their fragmentless requested URL.
+    fn get_fetch_metadata_cache(&self) -> Option<&dyn ThreadSafeCacheTrait<Url, FetchMetadata>> {
+        None
+    }
+
+    /// Optional cache of the opened archives, keyed by their URL, such that lookups of their entries do not open them again.
+    fn get_archives_cache(&self) -> Option<&dyn ThreadSafeCacheTrait<Url, Archive>> {
+        None
+    }
+
+    /// Optional watcher of the files loaded via `file://` URLs, whose changes invalidate the cached documents.
+    #[cfg(feature = "file-watcher")]
+    fn get_file_watcher(&self) -> Option<&FileWatcher> {
+        None
+    }
+
+    /// Optional policy expiring the cached documents.
+    fn get_expiration_policy(&self) -> Option<&ExpirationPolicy> {
+        None
+    }
+
+    /// Optional cache of the background refreshes of the stale documents, keyed by their URL (see [`ExpirationPolicy`]).
+    fn get_refreshes_cache(&self) -> Option<Arc<dyn ThreadSafeCacheTrait<Url, Refresh> + Send + Sync>> {
+        None
+    }
+
+    /// User-registered hooks run on every fetched document before caching it (see `LoaderTrait::post_load`), in registration order.
+    fn get_hooks(&self) -> &[LoadHook<T>] {
+        &[]
+    }
+
+    /// Optional dependencies between the cached documents (ie. of the archive entries on their archive), such that the
+    /// documents built on top of others are invalidated alongside them.
+    fn get_dependencies(&self) -> Option<&DocumentDependencies> {
+        None
+    }
+
+    /// Remove the document identified by `key`, and the value of its fragment (if any), from the caches.
+    ///
+    /// The values of the other fragments are recorded as dependents of the document (see `get_dependencies`), so they are
+    /// removed by `LoaderTrait::invalidate`.
+    fn remove_from_caches(&self, key: &Url) {
+        let fragmentless_url = remove_fragment_from_url(key);
+        let _d = self.get_cache().remove(&fragmentless_url);
+        if let Some(fragment_cache) = self.get_fragment_cache() {
+            let _d = fragment_cache.remove(key);
+        }
+        if let Some(fetch_metadata_cache) = self.get_fetch_metadata_cache() {
+            let _d = fetch_metadata_cache.remove(&fragmentless_url);
+        }
+        if let Some(archives_cache) = self.get_archives_cache() {
+            let _d = archives_cache.remove(&fragmentless_url);
+        }
+    }
 }
 
 pub trait GetClient<T> {
     fn get_client(&self) -> &Client;
+
+    /// Optional provider of the headers and credentials to send to the remote hosts.
+    fn get_credentials_provider(&self) -> Option<&CredentialsProvider> {
+        None
+    }
+
+    /// Timeouts of the requests of `url`.
+    fn get_timeouts(&self, _url: &Url) -> Timeouts {
+        Timeouts::default()
+    }
+
+    /// Client honoring the connect and read timeouts of `timeouts` (by default they are ignored in favour of `get_client`'s ones).
+    fn get_client_with_timeouts(&self, _timeouts: &Timeouts) -> Result<Client, LoaderError> {
+        Ok(self.get_client().clone())
+    }
+
+    /// Maximum size, in bytes, of the retrieved (and eventually decompressed) content of the documents and of the archive entries.
+    fn get_max_content_size(&self) -> usize {
+        DEFAULT_MAX_CONTENT_SIZE
+    }
 }
 
+/// Raw content of a document, alongside the metadata of its retrieval.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct FetchedContent {
+    pub content: Vec<u8>,
+    pub metadata: FetchMetadata,
+}
+
+/// Hook run on every fetched document before caching it, which could transform the document or reject it.
+pub type LoadHook<T> = Box<dyn Fn(&Url, T) -> Result<T, LoaderError> + Send + Sync>;
+
 #[allow(clippy::module_name_repetitions)]
 pub trait LoaderTrait<T>: Debug + GetClient<T> + GetCache<T> {
     fn get_from_cache(&self, key: &Url) -> Option<Arc<T>> {
@@ -26,6 +141,61 @@ pub trait LoaderTrait<T>: Debug + GetClient<T> + GetCache<T> {
         self.get_cache().set(key, value.clone())
     }
 
+    /// Record the metadata of the retrieval of the document requested via `url`.
+    ///
+    /// Documents are cached under the requested URL only, as the URL they were finally retrieved from (`FetchMetadata::url`)
+    /// could serve different content once requested directly.
+    fn save_fetch_metadata(&self, url: &Url, metadata: &FetchMetadata) {
+        if let Some(fetch_metadata_cache) = self.get_fetch_metadata_cache() {
+            fetch_metadata_cache.set(&remove_fragment_from_url(url), Arc::new(metadata.clone()));
+        }
+    }
+
+    /// URL the document requested via `url` was retrieved from (after following redirects), preserving the fragment of `url`.
+    ///
+    /// Relative references within the document are resolved against it, and it is `url` itself if no redirect was followed.
+    fn get_final_url(&self, url: &Url) -> Url {
+        self.get_fetch_metadata_cache()
+            .and_then(|fetch_metadata_cache| fetch_metadata_cache.get(&remove_fragment_from_url(url)))
+            .map_or_else(
+                || url.clone(),
+                |metadata| {
+                    let mut final_url = metadata.url.clone();
+                    final_url.set_fragment(url.fragment());
+                    final_url
+                },
+            )
+    }
+
+    /// Invalidate the cached document identified by `key`, and the documents built on top of it (see `get_dependencies`),
+    /// such that they are loaded again on the next lookup.
+    fn invalidate(&self, key: &Url) {
+        self.remove_from_caches(key);
+        if let Some(dependencies) = self.get_dependencies() {
+            for dependent_url in dependencies.remove(&remove_fragment_from_url(key)) {
+                self.invalidate(&dependent_url);
+            }
+        }
+    }
+
+    /// Invalidate the cached documents whose files changed since the last lookup (see the `file-watcher` feature).
+    fn invalidate_changed_files(&self) {
+        #[cfg(feature = "file-watcher")]
+        if let Some(file_watcher) = self.get_file_watcher() {
+            for url in file_watcher.take_changed_urls() {
+                self.invalidate(&url);
+            }
+        }
+    }
+
+    /// Invalidate the cached documents whose files changed since the last lookup (see `invalidate_changed_files`), and the
+    /// document identified by `key` (or the documents it is built on top of) if expired according to the expiration policy.
+    ///
+    /// Stale documents are refreshed in background instead, and the refreshed documents replace the cached ones once available.
+    fn invalidate_outdated(&self, key: &Url) {
+        invalidate_outdated_document(self, key);
+    }
+
     fn load_from_string(&self, content: &str) -> Result<T, LoaderError> {
         self.load_from_bytes(content.as_bytes())
     }
@@ -33,41 +203,361 @@ pub trait LoaderTrait<T>: Debug + GetClient<T> + GetCache<T> {
     fn load_from_bytes(&self, content: &[u8]) -> Result<T, LoaderError>;
 
     fn load(&self, url: &str) -> Result<Arc<T>, LoaderError> {
-        self.load_with_timeout(url, Duration::from_millis(30_000))
+        let timeout = self.get_timeouts(&parse_and_normalize_url(url)?).total;
+        self.load_with_timeout(url, timeout)
     }
 
     fn load_with_timeout(&self, url: &str, timeout: Duration) -> Result<Arc<T>, LoaderError> {
         let url = parse_and_normalize_url(url)?;
 
-        Ok(Arc::new(if url.scheme() == "file" {
-            self.load_from_bytes(std::fs::read(url.to_file_path().unwrap())?.as_slice())
+        let fetched_content = self.fetch(&url, timeout)?;
+        let value = self.load_from_bytes(fetched_content.content.as_slice())?;
+        let value = self.post_load(&fetched_content.metadata.url, value)?;
+        self.save_fetch_metadata(&url, &fetched_content.metadata);
+        Ok(Arc::new(value))
+    }
+
+    /// Transform the value loaded from `url` before it gets cached (ie. interpolating environment variables).
+    ///
+    /// It runs once per fetched document, so its failures are not cached. The default implementation runs
+    /// the user-registered hooks (see `get_hooks`), which implementations overriding it are expected to run too.
+    fn post_load(&self, url: &Url, value: T) -> Result<T, LoaderError> {
+        self.get_hooks().iter().try_fold(value, |value, hook| hook(url, value))
+    }
+
+    /// Retrieve the raw content identified by `url` (`file://` URLs are read from the local file system).
+    ///
+    /// `timeout` bounds the whole request, while the connect and read timeouts are the ones returned by `get_timeouts`.
+    fn fetch_with_timeout(&self, url: &Url, timeout: Duration) -> Result<Vec<u8>, LoaderError> {
+        self.fetch(url, timeout).map(|fetched_content| fetched_content.content)
+    }
+
+    /// Retrieve the raw content identified by `url` and the metadata of its retrieval, following the redirects allowed by the client.
+    ///
+    /// Compressed content is transparently decompressed (see the `decompression-*` features), and entries of zip and tar
+    /// archives are identified by `jar:<archive URL>!/<entry path>`, `zip+<archive URL>!/<entry path>` or
+    /// `tar+<archive URL>!/<entry path>` URLs (see the `archive-*` features).
+    fn fetch(&self, url: &Url, timeout: Duration) -> Result<FetchedContent, LoaderError> {
+        self.fetch_if_modified(url, timeout, None)?.ok_or_else(|| {
+            LoaderError::IOError(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("Unexpected 304 Not Modified response to the unconditional request of {}", url),
+            ))
+        })
+    }
+
+    /// Same as `fetch`, but issuing a conditional request if `validators`, the metadata of a previous retrieval, recorded
+    /// the `ETag` or `Last-Modified` response headers; `None` is returned if the content was not modified since then.
+    ///
+    /// Local content (`file://` URLs and archive entries) is always retrieved.
+    fn fetch_if_modified(&self, url: &Url, timeout: Duration, validators: Option<&FetchMetadata>) -> Result<Option<FetchedContent>, LoaderError> {
+        if let Some(refreshes_cache) = self.get_refreshes_cache() {
+            let fragmentless_url = remove_fragment_from_url(url);
+            if let Some(Refresh::Modified(fetched_content)) = refreshes_cache.get(&fragmentless_url).as_deref() {
+                let _d = refreshes_cache.remove(&fragmentless_url);
+                return Ok(Some(fetched_content.clone()));
+            }
+        }
+
+        let started_at = Instant::now();
+        let max_content_size = self.get_max_content_size();
+        let local_content = if let Some((archive_url, entry_path)) = split_archive_url(url)? {
+            let entry_content = self.get_or_open_archive(&archive_url, timeout)?.read_entry(&entry_path, max_content_size)?;
+            if let Some(dependencies) = self.get_dependencies() {
+                dependencies.add(&remove_fragment_from_url(url), &archive_url);
+            }
+            Some(entry_content)
+        } else if url.scheme() == "file" {
+            let path = url.to_file_path().unwrap();
+            // The file is watched before being read, so changes happened while reading it are not missed
+            #[cfg(feature = "file-watcher")]
+            if let Some(file_watcher) = self.get_file_watcher() {
+                file_watcher.watch(&remove_fragment_from_url(url), &path)?;
+            }
+            Some(std::fs::read(&path)?)
         } else {
-            self.load_from_bytes(self.get_client().get(url.as_ref()).timeout(timeout).send()?.error_for_status()?.bytes()?.as_ref())
-        }?))
+            None
+        };
+        if let Some(local_content) = local_content {
+            let content = decompress(url, None, local_content, max_content_size)?;
+            return Ok(Some(FetchedContent {
+                metadata: FetchMetadata::without_response(remove_fragment_from_url(url), content.len(), started_at.elapsed()),
+                content,
+            }));
+        }
+
+        let timeouts = self.get_timeouts(url).with_total(timeout);
+        let client = self.get_client_with_timeouts(&timeouts)?;
+        fetch_remote(&client, self.get_credentials_provider(), url, &timeouts, max_content_size, validators)
+    }
+
+    /// Check, via a conditional request, whether the remote document identified by `key` changed since it was fetched, in
+    /// which case the cached document is invalidated (see `invalidate`) and `true` is returned. The next lookup of the
+    /// document loads the content retrieved by the conditional request, if the loader has a refreshes cache.
+    ///
+    /// Only documents retrieved via HTTP alongside the `ETag` or `Last-Modified` response headers could be revalidated, while
+    /// the documents built on top of others (ie. archive entries, see `get_dependencies`) are revalidated via them.
+    fn revalidate(&self, key: &Url) -> Result<bool, LoaderError> {
+        let fragmentless_url = remove_fragment_from_url(key);
+        let mut is_dependency_modified = false;
+        for dependency_url in self.get_dependencies().map_or_else(Vec::new, |dependencies| dependencies.dependencies(&fragmentless_url)) {
+            // Modified dependencies invalidate the document too
+            is_dependency_modified |= self.revalidate(&dependency_url)?;
+        }
+        let metadata = match self.get_fetch_metadata_cache().and_then(|fetch_metadata_cache| fetch_metadata_cache.get(&fragmentless_url)) {
+            Some(metadata) if metadata.status.is_some() && (metadata.header(ETAG.as_str()).is_some() || metadata.header(LAST_MODIFIED.as_str()).is_some()) => metadata,
+            _ => return Ok(is_dependency_modified),
+        };
+        let timeout = self.get_timeouts(&fragmentless_url).total;
+        match self.fetch_if_modified(&fragmentless_url, timeout, Some(&metadata))? {
+            Some(fetched_content) => {
+                // Loaders could load documents differently than fetching them (ie. `!include`s), so the modified content
+                // is used by the next fetch of the document instead of being loaded right away
+                self.invalidate(key);
+                if let Some(refreshes_cache) = self.get_refreshes_cache() {
+                    refreshes_cache.set(&fragmentless_url, Arc::new(Refresh::Modified(fetched_content)));
+                }
+                Ok(true)
+            }
+            None => Ok(is_dependency_modified),
+        }
+    }
+
+    /// Subscribe to the changes of the value identified by `key`, which is looked up again every `poll_interval` by a
+    /// background thread (see [`Subscription`]).
+    fn subscribe(self: Arc<Self>, key: &Url, poll_interval: Duration) -> Result<Subscription<T>, LoaderError>
+    where
+        Self: Sized + Send + Sync + 'static,
+        T: Send + Sync + 'static,
+    {
+        Subscription::new(self, key, poll_interval)
+    }
+
+    /// Retrieve the archive at `archive_url`, which is opened once if the archives cache is available.
+    ///
+    /// The metadata of the retrieval of the archive is recorded as for any other document, so the archive (and its entries,
+    /// which depend on it) expires and is revalidated as well.
+    fn get_or_open_archive(&self, archive_url: &Url, timeout: Duration) -> Result<Arc<Archive>, LoaderError> {
+        let archives_cache = self.get_archives_cache();
+        if let Some(archive) = archives_cache.and_then(|archives_cache| archives_cache.get(archive_url)) {
+            return Ok(archive);
+        }
+        let fetched_content = self.fetch(archive_url, timeout)?;
+        let archive = Arc::new(Archive::open(fetched_content.content)?);
+        self.save_fetch_metadata(archive_url, &fetched_content.metadata);
+        if let Some(archives_cache) = archives_cache {
+            archives_cache.set(archive_url, archive.clone());
+        }
+        Ok(archive)
     }
 
     fn get_or_fetch_with_result(&self, key: &Url) -> Result<Arc<T>, LoaderError> {
+        self.get_or_fetch_with_cache_status(key).map(|(value, _)| value)
+    }
+
+    /// Same as `get_or_fetch_with_result`, but alongside whether the value was retrieved from the caches (`true`) or its
+    /// document was fetched (`false`), once the outdated documents are invalidated.
+    ///
+    /// Loaders customizing the lookups are expected to override this method, on which `get_or_fetch_with_result` relies.
+    fn get_or_fetch_with_cache_status(&self, key: &Url) -> Result<(Arc<T>, bool), LoaderError> {
+        self.invalidate_outdated(key);
+        let fragment_cache = key.fragment().and_then(|_| self.get_fragment_cache());
+        if let Some(arc_fragment_value) = fragment_cache.and_then(|fragment_cache| fragment_cache.get(key)) {
+            return Ok((arc_fragment_value, true));
+        }
+
         let fragmentless_url = &remove_fragment_from_url(key);
-        let value = if let Some(arc_value) = self.get_from_cache(fragmentless_url) {
-            arc_value
+        let (value, from_cache) = if let Some(arc_value) = self.get_from_cache(fragmentless_url) {
+            (arc_value, true)
         } else {
             let arc_value = self.load(key.as_str())?;
             self.save_in_cache(fragmentless_url, &arc_value);
-            arc_value
+            (arc_value, false)
         };
         #[allow(clippy::option_if_let_else)]
         if let Some(fragment) = key.fragment() {
-            self.extract_fragment(fragment, value)
+            let arc_fragment_value = self.extract_fragment(&self.get_final_url(fragmentless_url), fragment, value)?;
+            if let Some(fragment_cache) = fragment_cache {
+                fragment_cache.set(key, arc_fragment_value.clone());
+                if let Some(dependencies) = self.get_dependencies() {
+                    dependencies.add(key, fragmentless_url);
+                }
+            }
+            Ok((arc_fragment_value, from_cache))
         } else {
-            Ok(value)
+            Ok((value, from_cache))
         }
     }
 
-    fn extract_fragment(&self, _fragment: &str, value: Arc<T>) -> Result<Arc<T>, LoaderError> {
+    /// Same as `get_or_fetch_with_result`, but alongside the URL the document was finally retrieved from (see `get_final_url`)
+    /// and the metadata of its retrieval (ie. for diagnostics, or to dispatch on the content type).
+    fn get_or_fetch_loaded(&self, key: &Url) -> Result<Loaded<T>, LoaderError> {
+        let fragmentless_url = remove_fragment_from_url(key);
+        let (value, from_cache) = self.get_or_fetch_with_cache_status(key)?;
+        Ok(Loaded {
+            value,
+            url: self.get_final_url(key),
+            metadata: self.get_fetch_metadata_cache().and_then(|fetch_metadata_cache| fetch_metadata_cache.get(&fragmentless_url)),
+            from_cache,
+        })
+    }
+
+    fn extract_fragment(&self, _key: &Url, _fragment: &str, value: Arc<T>) -> Result<Arc<T>, LoaderError> {
         Ok(value)
     }
 }
 
+/// Default implementation of `LoaderTrait::invalidate_outdated`, such that loaders overriding it can still rely on it.
+pub(in crate) fn invalidate_outdated_document<T, L: LoaderTrait<T> + ?Sized>(loader: &L, key: &Url) {
+    loader.invalidate_changed_files();
+    // Outdated documents invalidate the documents built on top of them (see `LoaderTrait::invalidate`)
+    if let Some(dependencies) = loader.get_dependencies() {
+        for dependency_url in dependencies.dependencies(&remove_fragment_from_url(key)) {
+            invalidate_outdated_document(loader, &dependency_url);
+        }
+    }
+    let expiration_policy = match loader.get_expiration_policy() {
+        Some(expiration_policy) => expiration_policy,
+        None => return,
+    };
+    let fragmentless_url = remove_fragment_from_url(key);
+    let metadata = match loader
+        .get_fetch_metadata_cache()
+        .and_then(|fetch_metadata_cache| fetch_metadata_cache.get(&fragmentless_url))
+    {
+        Some(metadata) => metadata,
+        None => return,
+    };
+
+    let refreshes_cache = loader.get_refreshes_cache();
+    let refresh = refreshes_cache.as_ref().and_then(|refreshes_cache| refreshes_cache.get(&fragmentless_url));
+    match refresh.as_deref() {
+        // The modified content is used by the next fetch of the document
+        Some(Refresh::Modified(_)) => {
+            loader.invalidate(key);
+            return;
+        }
+        Some(Refresh::NotModified) => {
+            let _d = refreshes_cache.as_ref().and_then(|refreshes_cache| refreshes_cache.remove(&fragmentless_url));
+            let mut metadata = FetchMetadata::clone(&metadata);
+            metadata.fetched_at = Instant::now();
+            loader.save_fetch_metadata(&fragmentless_url, &metadata);
+            return;
+        }
+        Some(Refresh::Pending | Refresh::Failed { .. }) | None => {}
+    }
+
+    match (expiration_policy.freshness_since(metadata.fetched_at), refreshes_cache) {
+        (Freshness::Fresh, _) => {}
+        // Local documents are cheap to retrieve, so they are not refreshed in background
+        (Freshness::Stale, Some(refreshes_cache)) if metadata.status.is_some() => {
+            let timeouts = loader.get_timeouts(&fragmentless_url);
+            if Refresh::is_due(refresh.as_deref()) {
+                if let Ok(client) = loader.get_client_with_timeouts(&timeouts) {
+                    refresh_in_background(
+                        &refreshes_cache,
+                        client,
+                        loader.get_credentials_provider().cloned(),
+                        &fragmentless_url,
+                        timeouts,
+                        loader.get_max_content_size(),
+                        metadata,
+                    );
+                }
+            }
+        }
+        (Freshness::Stale | Freshness::Expired, refreshes_cache) => {
+            // Pending refreshes would be older than the document fetched again
+            if let Some(refreshes_cache) = refreshes_cache {
+                let _d = refreshes_cache.remove(&fragmentless_url);
+            }
+            loader.invalidate(key);
+        }
+    }
+}
+
+/// Retrieve the remote content identified by `url` (see `LoaderTrait::fetch_if_modified`).
+pub(in crate) fn fetch_remote(
+    client: &Client,
+    credentials_provider: Option<&CredentialsProvider>,
+    url: &Url,
+    timeouts: &Timeouts,
+    max_content_size: usize,
+    validators: Option<&FetchMetadata>,
+) -> Result<Option<FetchedContent>, LoaderError> {
+    let started_at = Instant::now();
+    let timed_out = |bound, timeout| {
+        LoaderError::IOError(std::io::Error::new(
+            std::io::ErrorKind::TimedOut,
+            format!("{} of {} exceeded the timeout of {:?}", bound, url, timeout),
+        ))
+    };
+    // The request timeout bounds the whole request, up to the end of the response body
+    let mut request = client.get(url.as_ref()).timeout(timeouts.total).header(ACCEPT_ENCODING, accept_encoding());
+    if let Some(credentials_provider) = credentials_provider {
+        request = credentials_provider.authenticate(url, request);
+    }
+    if let Some(validators) = validators {
+        if let Some(etag) = validators.header(ETAG.as_str()) {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = validators.header(LAST_MODIFIED.as_str()) {
+            request = request.header(IF_MODIFIED_SINCE, last_modified);
+        }
+    }
+    let mut response = request.send()?.error_for_status()?;
+    if validators.is_some() && response.status() == StatusCode::NOT_MODIFIED {
+        return Ok(None);
+    }
+    let final_url = remove_fragment_from_url(response.url());
+    let status = response.status().as_u16();
+    let header_value = |name| response.headers().get(name).and_then(|value| value.to_str().ok()).map(ToString::to_string);
+    let content_type = header_value(CONTENT_TYPE.as_str());
+    // The Content-Encoding header is removed by the HTTP client if it decompresses the content itself (gzip)
+    let content_encoding = header_value(CONTENT_ENCODING.as_str());
+    let headers = METADATA_HEADERS
+        .iter()
+        .filter_map(|name| header_value(*name).map(|value| ((*name).to_string(), value)))
+        .collect();
+
+    // The reads blocked for longer than the client timeout are interrupted by the client (see `ClientConfig::with_read_timeout`),
+    // the deadlines are checked after every read too, such that they hold regardless of the client in use
+    let mut content = Vec::new();
+    let mut buffer = [0; 8192];
+    loop {
+        let read_started_at = Instant::now();
+        let read_bytes = response.read(&mut buffer)?;
+        if let Some(read_timeout) = timeouts.read {
+            if read_started_at.elapsed() > read_timeout {
+                return Err(timed_out("Read of the response", read_timeout));
+            }
+        }
+        if started_at.elapsed() > timeouts.total {
+            return Err(timed_out("Request", timeouts.total));
+        }
+        if read_bytes == 0 {
+            let content = decompress(&final_url, content_encoding.as_deref(), content, max_content_size)?;
+            return Ok(Some(FetchedContent {
+                metadata: FetchMetadata {
+                    url: final_url,
+                    status: Some(status),
+                    content_type,
+                    headers,
+                    size: content.len(),
+                    duration: started_at.elapsed(),
+                    fetched_at: Instant::now(),
+                },
+                content,
+            }));
+        }
+        // The content could have been decompressed by the HTTP client already (gzip), so it is bounded while being read too
+        if content.len() + read_bytes > max_content_size {
+            return Err(content_too_large_error(url, max_content_size));
+        }
+        content.extend_from_slice(&buffer[..read_bytes]);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::{remove_fragment_from_url, LoaderTrait};
@@ -0,0 +1,109 @@
+use crate::loader::error::LoaderError;
+use notify::{DebouncedEvent, RecommendedWatcher, RecursiveMode, Watcher};
+use parking_lot::Mutex;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::{Debug, Error, Formatter},
+    path::{Path, PathBuf},
+    sync::{mpsc::channel, Arc},
+    thread,
+    time::Duration,
+};
+use url::Url;
+
+/// Callback invoked, from the watcher thread, with the URL of every watched file that changed.
+pub(in crate) type FileChangeCallback = Box<dyn Fn(&Url) + Send + Sync>;
+
+impl From<notify::Error> for LoaderError {
+    fn from(error: notify::Error) -> Self {
+        match error {
+            notify::Error::Io(io_error) => Self::IOError(io_error),
+            error => Self::IOError(std::io::Error::new(std::io::ErrorKind::Other, error.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct WatchedFiles {
+    // Watched files, keyed by their canonical path, and the (normalized) URLs they are loaded via (ie. through symbolic links)
+    urls: HashMap<PathBuf, HashSet<Url>>,
+    directories: HashSet<PathBuf>,
+    // URLs of the files changed since the last call of FileWatcher::take_changed_urls
+    changed_urls: HashSet<Url>,
+}
+
+/// Watcher of the files loaded via `file://` URLs, which tracks the files changed since the last lookup such that their
+/// cached documents are invalidated (and loaded again on the next lookup).
+///
+/// The parent directories of the files are watched, as editors frequently replace the files instead of writing them in place.
+pub struct FileWatcher {
+    watcher: Mutex<RecommendedWatcher>,
+    watched_files: Arc<Mutex<WatchedFiles>>,
+}
+
+impl Debug for FileWatcher {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        let watched_files = self.watched_files.lock();
+        f.debug_struct("FileWatcher")
+            .field("urls", &watched_files.urls.values().flatten().collect::<Vec<_>>())
+            .field("changed_urls", &watched_files.changed_urls)
+            .finish_non_exhaustive()
+    }
+}
+
+impl FileWatcher {
+    /// Start watching files, reporting the changes happened within `delay` as a single one.
+    pub(in crate) fn new(delay: Duration, on_change: FileChangeCallback) -> Result<Self, LoaderError> {
+        let (sender, receiver) = channel();
+        let watcher = notify::watcher(sender, delay)?;
+        let watched_files = Arc::new(Mutex::new(WatchedFiles::default()));
+
+        let thread_watched_files = watched_files.clone();
+        // The thread terminates once the watcher, and so the sender, is dropped
+        let _d = thread::Builder::new().name("loader-rs-file-watcher".to_string()).spawn(move || {
+            for event in receiver {
+                let paths = match event {
+                    DebouncedEvent::Create(path) | DebouncedEvent::Write(path) | DebouncedEvent::Remove(path) => vec![path],
+                    DebouncedEvent::Rename(from_path, to_path) => vec![from_path, to_path],
+                    _ => continue,
+                };
+                let changed_urls: Vec<Url> = {
+                    let mut watched_files = thread_watched_files.lock();
+                    let changed_urls: Vec<Url> = paths.iter().filter_map(|path| watched_files.urls.get(path)).flatten().cloned().collect();
+                    watched_files.changed_urls.extend(changed_urls.iter().cloned());
+                    changed_urls
+                };
+                // The callback is invoked without holding the lock, so it could look the changed documents up
+                for changed_url in &changed_urls {
+                    on_change(changed_url);
+                }
+            }
+        })?;
+
+        Ok(Self {
+            watcher: Mutex::new(watcher),
+            watched_files,
+        })
+    }
+
+    /// Watch the file at `path`, loaded via `url`.
+    ///
+    /// The changes of a file loaded via several URLs are reported for every one of them.
+    pub(in crate) fn watch(&self, url: &Url, path: &Path) -> Result<(), LoaderError> {
+        let path = path.canonicalize()?;
+        let mut watched_files = self.watched_files.lock();
+        if let Some(directory) = path.parent() {
+            if !watched_files.directories.contains(directory) {
+                self.watcher.lock().watch(directory, RecursiveMode::NonRecursive)?;
+                let _d = watched_files.directories.insert(directory.to_path_buf());
+            }
+        }
+        let _d = watched_files.urls.entry(path).or_default().insert(url.clone());
+        Ok(())
+    }
+
+    /// URLs of the watched files changed since the last call.
+    pub(in crate) fn take_changed_urls(&self) -> HashSet<Url> {
+        std::mem::take(&mut self.watched_files.lock().changed_urls)
+    }
+}
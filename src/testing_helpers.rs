@@ -40,6 +40,9 @@ pub struct MockLoaderRequest {
     #[builder(default = "\"GET\".to_string()")]
     #[builder(setter(into))]
     http_verb: String,
+    #[builder(default = "Vec::new()")]
+    #[builder(setter(into))]
+    req_headers: Vec<(&'static str, &'static str)>,
     #[builder(default = "None")]
     #[builder(setter(into))]
     resp_content_type: Option<String>,
@@ -63,6 +66,10 @@ impl MockLoaderRequest {
         )
         .with_status(self.resp_status_code);
 
+        for (name, value) in &self.req_headers {
+            mocked_request_builder = mocked_request_builder.match_header(name, *value);
+        }
+
         if let Some(content_type) = &self.resp_content_type {
             mocked_request_builder = mocked_request_builder.with_header("content-type", content_type);
         }
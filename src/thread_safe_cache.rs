@@ -10,6 +10,10 @@ use std::{
 pub trait ThreadSafeCacheTrait<K: Clone + Eq + Hash, V> {
     fn set(&self, key: &K, value: Arc<V>);
     fn get(&self, key: &K) -> Option<Arc<V>>;
+    fn remove(&self, key: &K) -> Option<Arc<V>>;
+    fn clear(&self);
+    /// Atomically set `value` if `key` is still cached with `current` (compared by pointer), returning whether it was set.
+    fn compare_and_set(&self, key: &K, current: &Arc<V>, value: Arc<V>) -> bool;
 }
 
 #[allow(clippy::module_name_repetitions)]
@@ -42,4 +46,21 @@ impl<K: Clone + Eq + Hash, V> ThreadSafeCacheTrait<K, V> for ThreadSafeCacheImpl
     fn get(&self, key: &K) -> Option<Arc<V>> {
         self.0.lock().cache_get(key).cloned()
     }
+
+    fn remove(&self, key: &K) -> Option<Arc<V>> {
+        self.0.lock().cache_remove(key)
+    }
+
+    fn clear(&self) {
+        self.0.lock().cache_clear();
+    }
+
+    fn compare_and_set(&self, key: &K, current: &Arc<V>, value: Arc<V>) -> bool {
+        let mut cache_lock = self.0.lock();
+        if !cache_lock.cache_get(key).map_or(false, |cached| Arc::ptr_eq(cached, current)) {
+            return false;
+        }
+        let _d = cache_lock.cache_set(key.clone(), value);
+        true
+    }
 }
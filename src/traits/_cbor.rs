@@ -0,0 +1,115 @@
+use crate::{json::ConcreteJsonLoader, loader::error::LoaderError, traits::format::Format};
+use ciborium::value::Value as CborValue;
+use serde_json::{Map, Number, Value};
+use std::convert::TryFrom;
+
+/// [CBOR](https://cbor.io/) binary format.
+///
+/// Tagged values are loaded as the value they tag, and integer map keys as their decimal representation.
+/// Byte strings, non-finite floats and other map keys have no JSON counterpart, so they are rejected.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CborFormat;
+
+#[allow(clippy::module_name_repetitions)]
+pub type CborLoader = ConcreteJsonLoader<Value, CborFormat>;
+
+fn cbor_key_to_string(key: CborValue) -> Result<String, LoaderError> {
+    match key {
+        CborValue::Text(text_value) => Ok(text_value),
+        CborValue::Integer(integer_value) => Ok(i128::from(integer_value).to_string()),
+        key => Err(LoaderError::FormatError(format!("CBOR map key {:?} cannot be represented as JSON object key", key))),
+    }
+}
+
+fn cbor_to_json(value: CborValue) -> Result<Value, LoaderError> {
+    Ok(match value {
+        CborValue::Integer(integer_value) => i64::try_from(integer_value)
+            .map(Value::from)
+            .or_else(|_| u64::try_from(integer_value).map(Value::from))
+            .map_err(|_| LoaderError::FormatError(format!("{} cannot be represented as JSON number", i128::from(integer_value))))?,
+        CborValue::Float(float_value) => {
+            Value::Number(Number::from_f64(float_value).ok_or_else(|| LoaderError::FormatError(format!("{} cannot be represented as JSON number", float_value)))?)
+        }
+        CborValue::Text(text_value) => Value::String(text_value),
+        CborValue::Bool(boolean_value) => Value::Bool(boolean_value),
+        CborValue::Null => Value::Null,
+        // Tags (ie. dates) carry the semantics of the tagged value, which is loaded as is
+        CborValue::Tag(_, tagged_value) => cbor_to_json(*tagged_value)?,
+        CborValue::Array(items) => Value::Array(items.into_iter().map(cbor_to_json).collect::<Result<_, _>>()?),
+        CborValue::Map(entries) => Value::Object(
+            entries
+                .into_iter()
+                .map(|(key, value)| Ok((cbor_key_to_string(key)?, cbor_to_json(value)?)))
+                .collect::<Result<Map<_, _>, LoaderError>>()?,
+        ),
+        CborValue::Bytes(_) => return Err(LoaderError::FormatError("CBOR byte strings cannot be represented as JSON values".to_string())),
+        value => return Err(LoaderError::FormatError(format!("CBOR value {:?} cannot be represented as JSON value", value))),
+    })
+}
+
+impl Format for CborFormat {
+    fn parse(content: &[u8]) -> Result<Value, LoaderError> {
+        ciborium::de::from_reader(content)
+            .map_err(|cbor_error| LoaderError::from(&cbor_error))
+            .and_then(cbor_to_json)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CborFormat, CborLoader};
+    use crate::{
+        loader::error::LoaderError,
+        testing_helpers::MockLoaderRequestBuilder,
+        traits::{check_loader, format::Format},
+    };
+    use serde_json::Value;
+    use test_case::test_case;
+
+    #[test]
+    fn test_is_loader() {
+        check_loader::<_, CborLoader>()
+    }
+
+    #[test_case("Object.cbor", "", &json![{"key": "Some Text", "list": [1, 2]}])]
+    #[test_case("Object.cbor", "/key", &json!["Some Text"])]
+    #[test_case("Object.cbor", "/list/1", &json![2])]
+    fn test_load_from_file_valid_content(file_name: &'static str, fragment: &str, expected_loaded_object: &Value) {
+        assert_eq!(
+            &*MockLoaderRequestBuilder::default()
+                .http_path(format!("/#{}", fragment))
+                .resp_body_file_path(vec![file_name])
+                .build()
+                .unwrap()
+                .send_request(&CborLoader::default())
+                .unwrap(),
+            expected_loaded_object,
+        );
+    }
+
+    #[test]
+    fn test_load_invalid_content() {
+        assert!(matches!(
+            MockLoaderRequestBuilder::default()
+                .resp_body_file_path(vec!["Invalid.cbor"])
+                .build()
+                .unwrap()
+                .send_request(&CborLoader::default())
+                .unwrap_err(),
+            LoaderError::FormatError(_)
+        ));
+    }
+
+    #[test_case(&[0xa1, 0x01, 0x61, 0x61] => Ok(json![{"1": "a"}]) ; "integer_key")]
+    #[test_case(&[0xc1, 0x00] => Ok(json![0]) ; "tagged_value")]
+    #[test_case(&[0x1b, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff] => Ok(json![u64::MAX]) ; "large_integer")]
+    #[test_case(&[0x3b, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff] => Err("-18446744073709551616 cannot be represented as JSON number".to_string()) ; "too_small_integer")]
+    #[test_case(&[0x41, 0x01] => Err("CBOR byte strings cannot be represented as JSON values".to_string()) ; "byte_string")]
+    #[test_case(&[0xa1, 0xf5, 0x01] => Err("CBOR map key Bool(true) cannot be represented as JSON object key".to_string()) ; "boolean_key")]
+    fn test_parse(content: &[u8]) -> Result<Value, String> {
+        CborFormat::parse(content).map_err(|loader_error| match loader_error {
+            LoaderError::FormatError(message) => message,
+            loader_error => panic!("Expected LoaderError::FormatError, received {:?}", loader_error),
+        })
+    }
+}
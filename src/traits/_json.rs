@@ -1,9 +1,10 @@
 use crate::{
-    json::{extract_fragment_json_loader, ConcreteJsonLoader},
+    json::ConcreteJsonLoader,
     loader::{error::LoaderError, trait_::LoaderTrait},
 };
 use json::{Error, JsonValue};
 use std::sync::Arc;
+use url::Url;
 
 #[allow(clippy::module_name_repetitions)]
 pub type JsonLoader = ConcreteJsonLoader<JsonValue>;
@@ -16,9 +17,7 @@ impl From<Error> for LoaderError {
 }
 
 impl LoaderTrait<JsonValue> for JsonLoader {
-    fn extract_fragment(&self, fragment: &str, value: Arc<JsonValue>) -> Result<Arc<JsonValue>, LoaderError> {
-        extract_fragment_json_loader(fragment, &value)
-    }
+    json_loader_methods!(JsonValue);
 
     fn load_from_string(&self, content: &str) -> Result<JsonValue, LoaderError>
     where
@@ -0,0 +1,59 @@
+use crate::{json::ConcreteJsonLoader, loader::error::LoaderError, traits::format::Format};
+use serde_json::Value;
+
+/// [JSON5](https://json5.org/) format, a superset of JSON allowing comments, trailing commas, unquoted keys, etc.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Json5Format;
+
+#[allow(clippy::module_name_repetitions)]
+pub type Json5Loader = ConcreteJsonLoader<Value, Json5Format>;
+
+impl Format for Json5Format {
+    fn parse(content: &[u8]) -> Result<Value, LoaderError> {
+        let string_value = std::str::from_utf8(content).map_err(|utf8_error| LoaderError::from(&utf8_error))?;
+        json5::from_str(string_value).map_err(|json5_error| LoaderError::from(&json5_error))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Json5Loader;
+    use crate::{loader::error::LoaderError, testing_helpers::MockLoaderRequestBuilder, traits::check_loader};
+    use serde_json::Value;
+    use test_case::test_case;
+
+    #[test]
+    fn test_is_loader() {
+        check_loader::<_, Json5Loader>()
+    }
+
+    #[test_case("Boolean.json", "", &json![false])]
+    #[test_case("Object.json", "", &json![{"key": "Some Text"}])]
+    #[test_case("Object.json5", "", &json![{"key": "Some Text", "list": [1, 2], "quoted": "single quotes"}])]
+    #[test_case("Object.json5", "/list/1", &json![2])]
+    fn test_load_from_file_valid_content(file_name: &'static str, fragment: &str, expected_loaded_object: &Value) {
+        assert_eq!(
+            &*MockLoaderRequestBuilder::default()
+                .http_path(format!("/#{}", fragment))
+                .resp_body_file_path(vec![file_name])
+                .build()
+                .unwrap()
+                .send_request(&Json5Loader::default())
+                .unwrap(),
+            expected_loaded_object,
+        );
+    }
+
+    #[test]
+    fn test_load_invalid_content() {
+        assert!(matches!(
+            MockLoaderRequestBuilder::default()
+                .resp_body_file_path(vec!["Invalid.json"])
+                .build()
+                .unwrap()
+                .send_request(&Json5Loader::default())
+                .unwrap_err(),
+            LoaderError::FormatError(_)
+        ));
+    }
+}
@@ -0,0 +1,59 @@
+use crate::{json::ConcreteJsonLoader, loader::error::LoaderError, traits::format::Format};
+use serde_json::Value;
+
+/// [MessagePack](https://msgpack.org/) binary format.
+///
+/// Binary values and non-string map keys have no JSON counterpart, so they are rejected.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MessagePackFormat;
+
+#[allow(clippy::module_name_repetitions)]
+pub type MessagePackLoader = ConcreteJsonLoader<Value, MessagePackFormat>;
+
+impl Format for MessagePackFormat {
+    fn parse(content: &[u8]) -> Result<Value, LoaderError> {
+        rmp_serde::from_slice(content).map_err(|msgpack_error| LoaderError::from(&msgpack_error))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MessagePackLoader;
+    use crate::{loader::error::LoaderError, testing_helpers::MockLoaderRequestBuilder, traits::check_loader};
+    use serde_json::Value;
+    use test_case::test_case;
+
+    #[test]
+    fn test_is_loader() {
+        check_loader::<_, MessagePackLoader>()
+    }
+
+    #[test_case("Object.msgpack", "", &json![{"key": "Some Text", "list": [1, 2]}])]
+    #[test_case("Object.msgpack", "/key", &json!["Some Text"])]
+    #[test_case("Object.msgpack", "/list/1", &json![2])]
+    fn test_load_from_file_valid_content(file_name: &'static str, fragment: &str, expected_loaded_object: &Value) {
+        assert_eq!(
+            &*MockLoaderRequestBuilder::default()
+                .http_path(format!("/#{}", fragment))
+                .resp_body_file_path(vec![file_name])
+                .build()
+                .unwrap()
+                .send_request(&MessagePackLoader::default())
+                .unwrap(),
+            expected_loaded_object,
+        );
+    }
+
+    #[test]
+    fn test_load_invalid_content() {
+        assert!(matches!(
+            MockLoaderRequestBuilder::default()
+                .resp_body_file_path(vec!["Invalid.msgpack"])
+                .build()
+                .unwrap()
+                .send_request(&MessagePackLoader::default())
+                .unwrap_err(),
+            LoaderError::FormatError(_)
+        ));
+    }
+}
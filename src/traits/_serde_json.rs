@@ -1,17 +1,30 @@
+#[cfg(feature = "serde-loader")]
+use crate::traits::format::Format;
 use crate::{
-    json::{extract_fragment_json_loader, ConcreteJsonLoader},
+    json::ConcreteJsonLoader,
     loader::{error::LoaderError, trait_::LoaderTrait},
 };
 use serde_json::Value;
 use std::sync::Arc;
+use url::Url;
 
 #[allow(clippy::module_name_repetitions)]
 pub type SerdeJsonLoader = ConcreteJsonLoader<Value>;
 
-impl LoaderTrait<Value> for SerdeJsonLoader {
-    fn extract_fragment(&self, fragment: &str, value: Arc<Value>) -> Result<Arc<Value>, LoaderError> {
-        extract_fragment_json_loader(fragment, &value)
+/// Marker of the JSON format, used to load documents via [`SerdeLoader`](crate::traits::loaders::SerdeLoader).
+#[cfg(feature = "serde-loader")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct JsonFormat;
+
+#[cfg(feature = "serde-loader")]
+impl Format for JsonFormat {
+    fn parse(content: &[u8]) -> Result<Value, LoaderError> {
+        serde_json::from_slice(content).map_err(|serde_error| LoaderError::from(&serde_error))
     }
+}
+
+impl LoaderTrait<Value> for SerdeJsonLoader {
+    json_loader_methods!(Value);
 
     fn load_from_bytes(&self, content: &[u8]) -> Result<Value, LoaderError>
     where
@@ -24,9 +37,18 @@ impl LoaderTrait<Value> for SerdeJsonLoader {
 #[cfg(test)]
 mod tests {
     use super::SerdeJsonLoader;
-    use crate::{loader::error::LoaderError, testing_helpers::MockLoaderRequestBuilder, traits::check_loader};
+    use crate::{
+        json::EnvInterpolator,
+        loader::{error::LoaderError, trait_::LoaderTrait, Loader},
+        testing_helpers::MockLoaderRequestBuilder,
+        traits::check_loader,
+        url_helpers::{remove_fragment_from_url, UrlError},
+    };
     use serde_json::Value;
+    use std::sync::Arc;
     use test_case::test_case;
+    #[cfg(feature = "json-schema")]
+    use {crate::testing_helpers::test_data_file_path, url::Url};
 
     #[test]
     fn test_is_loader() {
@@ -64,4 +86,200 @@ mod tests {
             LoaderError::FormatError(value) if "EOF while parsing an object at line 2 column 0" == &value
         ));
     }
+
+    #[test]
+    fn test_load_with_env_interpolation() {
+        std::env::set_var("LOADER_RS_SERDE_JSON_DEFINED", "value");
+        assert_eq!(
+            &*MockLoaderRequestBuilder::default()
+                .resp_body(
+                    r#"{
+                        "defined": "${LOADER_RS_SERDE_JSON_DEFINED}",
+                        "default": "${LOADER_RS_SERDE_JSON_UNDEFINED:-default}",
+                        "list": ["${LOADER_RS_SERDE_JSON_UNDEFINED}"]
+                    }"#
+                )
+                .build()
+                .unwrap()
+                .send_request(&SerdeJsonLoader::default().with_env_interpolation(EnvInterpolator::default()))
+                .unwrap(),
+            &json![{"defined": "value", "default": "default", "list": [""]}],
+        );
+    }
+
+    #[test]
+    fn test_load_with_strict_env_interpolation() {
+        // Variables not set by any test, such that the outcome does not depend on the order tests run in
+        assert!(matches!(
+            MockLoaderRequestBuilder::default()
+                .resp_body(r#"{"default": "${LOADER_RS_SERDE_JSON_STRICT_UNDEFINED:-default}", "list": ["${LOADER_RS_SERDE_JSON_STRICT_UNDEFINED}"]}"#)
+                .build()
+                .unwrap()
+                .send_request(&SerdeJsonLoader::default().with_env_interpolation(EnvInterpolator::strict()))
+                .unwrap_err(),
+            LoaderError::FormatError(message) if message == "Environment variable 'LOADER_RS_SERDE_JSON_STRICT_UNDEFINED' is not defined"
+        ));
+    }
+
+    #[cfg(feature = "json-schema")]
+    #[test_case("Object.json" => Ok(()) ; "valid_document")]
+    #[test_case("String.json" => Err("#: Value is not of type \"object\"".to_string()) ; "invalid_type")]
+    #[test_case("Schema.json" => Err("#: Required property \"key\" is missing".to_string()) ; "missing_property")]
+    fn test_load_with_schema(file_name: &str) -> Result<(), String> {
+        let schema_url = Url::from_file_path(test_data_file_path(&["ObjectSchema.json"]).unwrap().as_path()).unwrap();
+        let loader = SerdeJsonLoader::default().with_schema(schema_url.clone());
+        let url = Url::from_file_path(test_data_file_path(&[file_name]).unwrap().as_path()).unwrap();
+        let result = loader.get_or_fetch_with_result(&url).map(|_| ()).map_err(|loader_error| match loader_error {
+            LoaderError::ValidationError(message) => message.replace(&format!("{} is not valid against {}: ", url, schema_url), ""),
+            loader_error => panic!("Expected LoaderError::ValidationError, received {:?}", loader_error),
+        });
+
+        // The schema is loaded via the same cache
+        assert!(loader.get_from_cache(&schema_url).is_some());
+        result
+    }
+
+    // The references of the redirected schema are resolved against the URL it was finally retrieved from, and the schema
+    // is retrieved once even if referencing itself
+    #[cfg(feature = "json-schema")]
+    #[test_case(r#"{"key": "Some Text"}"# => true ; "valid_document")]
+    #[test_case(r#"{"key": 1}"# => false ; "invalid_document")]
+    fn test_load_with_redirected_schema(document: &str) -> bool {
+        let _redirect_mock = mockito::mock("GET", "/schema.json")
+            .with_status(302)
+            .with_header("location", "/schemas/schema.json")
+            .create();
+        let schema_mock = mockito::mock("GET", "/schemas/schema.json")
+            .with_body(r##"{"$ref": "#/$defs/object", "$defs": {"object": {"type": "object", "properties": {"key": {"$ref": "key.json"}}}}}"##)
+            .expect(1)
+            .create();
+        let key_schema_mock = mockito::mock("GET", "/schemas/key.json").with_body(r#"{"type": "string"}"#).expect(1).create();
+        let _document_mock = mockito::mock("GET", "/document.json").with_body(document).create();
+        let loader = SerdeJsonLoader::default().with_schema(Url::parse(&format!("{}/schema.json", mockito::server_url())).unwrap());
+
+        let result = loader.get_or_fetch_with_result(&Url::parse(&format!("{}/document.json", mockito::server_url())).unwrap());
+        schema_mock.assert();
+        key_schema_mock.assert();
+        match result {
+            Ok(_) => true,
+            Err(LoaderError::ValidationError(_)) => false,
+            Err(loader_error) => panic!("Expected LoaderError::ValidationError, received {:?}", loader_error),
+        }
+    }
+
+    #[test_case("#foo", &json![{"$anchor": "foo", "type": "string"}] ; "anchor")]
+    #[test_case("#bar", &json![{"$id": "#bar", "type": "integer"}] ; "legacy_anchor")]
+    #[test_case("embedded.json", &json![{"$id": "embedded.json", "$defs": {"inner": {"$anchor": "foo", "type": "boolean"}}}] ; "embedded_resource")]
+    #[test_case("embedded.json#foo", &json![{"$anchor": "foo", "type": "boolean"}] ; "embedded_resource_anchor")]
+    #[test_case("embedded.json#/$defs/inner/type", &json!["boolean"] ; "embedded_resource_json_pointer")]
+    fn test_load_anchors_and_embedded_resources(relative_url: &str, expected_loaded_object: &Value) {
+        assert_eq!(
+            &*MockLoaderRequestBuilder::default()
+                .http_path("/schema.json")
+                .resp_body_file_path(vec!["Schema.json"])
+                .build()
+                .unwrap()
+                .run_in_mock_context(&|url| {
+                    let loader = SerdeJsonLoader::default().with_schema_resources();
+                    let resource = loader.get_or_fetch_resource_with_result(url, &url.join(relative_url).unwrap()).unwrap();
+
+                    // Embedded resources are visible only via the document embedding them
+                    assert!(loader.get_from_cache(&url.join("embedded.json").unwrap()).is_none());
+                    resource
+                }),
+            expected_loaded_object,
+        );
+    }
+
+    #[test_case(&SerdeJsonLoader::default() => Err("Plain-name fragment 'foo' of '{url}' is resolved only with schema resources enabled".to_string()) ; "without_schema_resources")]
+    #[test_case(&SerdeJsonLoader::default().with_schema_resources() => Ok(json![{"$anchor": "foo", "type": "string"}]) ; "with_schema_resources")]
+    fn test_load_anchor(loader: &SerdeJsonLoader) -> Result<Value, String> {
+        MockLoaderRequestBuilder::default()
+            .http_path("/schema.json#foo")
+            .resp_body_file_path(vec!["Schema.json"])
+            .build()
+            .unwrap()
+            .run_in_mock_context(&|url| {
+                loader
+                    .get_or_fetch_with_result(url)
+                    .map(|value| (*value).clone())
+                    .map_err(|loader_error| match loader_error {
+                        LoaderError::InvalidURL(UrlError::JsonFragmentError(message)) => message.replace(remove_fragment_from_url(url).as_str(), "{url}"),
+                        loader_error => panic!("Expected UrlError::JsonFragmentError, received {:?}", loader_error),
+                    })
+            })
+    }
+
+    #[test]
+    fn test_load_not_existing_anchor() {
+        assert!(matches!(
+            MockLoaderRequestBuilder::default()
+                .http_path("/schema.json#not-existing")
+                .resp_body_file_path(vec!["Schema.json"])
+                .build()
+                .unwrap()
+                .send_request(&SerdeJsonLoader::default().with_schema_resources())
+                .unwrap_err(),
+            LoaderError::InvalidURL(UrlError::JsonFragmentError(value)) if value.starts_with("Anchor 'not-existing' not found in ")
+        ));
+    }
+
+    #[cfg(feature = "json-path")]
+    #[test]
+    fn test_query_url() {
+        assert_eq!(
+            MockLoaderRequestBuilder::default()
+                .http_path("/schema.json#$..type")
+                .resp_body_file_path(vec!["Schema.json"])
+                .build()
+                .unwrap()
+                .run_in_mock_context(&|url| SerdeJsonLoader::default().query_url(url).unwrap()),
+            vec![Arc::new(json!["string"]), Arc::new(json!["boolean"]), Arc::new(json!["integer"])],
+        );
+    }
+
+    #[test_case("", &json![{"key": "Some Text"}], "" ; "whole_document")]
+    #[test_case("/key", &json!["Some Text"], "/key" ; "json_pointer")]
+    fn test_get_or_fetch_fragment_with_result(fragment: &str, expected_value: &Value, expected_json_pointer: &str) {
+        MockLoaderRequestBuilder::default()
+            .http_path(format!("/#{}", fragment))
+            .resp_body_file_path(vec!["Object.json"])
+            .expected_mock_calls(2_usize)
+            .build()
+            .unwrap()
+            .run_in_mock_context(&|url| {
+                let loader = SerdeJsonLoader::from(Loader::default().with_fragment_cache());
+                let fragment = loader.get_or_fetch_fragment_with_result(url).unwrap();
+
+                assert_eq!(&**fragment, expected_value);
+                assert_eq!(fragment.json_pointer(), expected_json_pointer);
+                let document = loader.get_or_fetch_with_result(&remove_fragment_from_url(url)).unwrap();
+                assert!(Arc::ptr_eq(fragment.document(), &document));
+                // The value is borrowed from the document, and the fragment is shared via the cache of the loader
+                assert!(std::ptr::eq(fragment.value(), document.pointer(expected_json_pointer).unwrap()));
+                assert!(Arc::ptr_eq(&fragment, &loader.get_or_fetch_fragment_with_result(url).unwrap()));
+
+                loader.invalidate(&remove_fragment_from_url(url));
+                let reloaded_fragment = loader.get_or_fetch_fragment_with_result(url).unwrap();
+                assert!(!Arc::ptr_eq(&fragment, &reloaded_fragment));
+                assert!(Arc::ptr_eq(
+                    reloaded_fragment.document(),
+                    &loader.get_or_fetch_with_result(&remove_fragment_from_url(url)).unwrap()
+                ));
+            });
+    }
+
+    #[test_case(&SerdeJsonLoader::default(), false ; "without_fragment_cache")]
+    #[test_case(&SerdeJsonLoader::from(Loader::default().with_fragment_cache()), true ; "with_fragment_cache")]
+    fn test_load_same_fragment_twice(loader: &SerdeJsonLoader, expected_same_allocation: bool) {
+        MockLoaderRequestBuilder::default()
+            .http_path("/#/key")
+            .resp_body_file_path(vec!["Object.json"])
+            .build()
+            .unwrap()
+            .run_in_mock_context(&|url| {
+                let value = loader.get_or_fetch_with_result(url).unwrap();
+                assert_eq!(Arc::ptr_eq(&value, &loader.get_or_fetch_with_result(url).unwrap()), expected_same_allocation);
+            });
+    }
 }
@@ -1,32 +1,226 @@
+#[cfg(feature = "serde-loader")]
+use crate::traits::format::Format;
 use crate::{
-    json::{extract_fragment_json_loader, ConcreteJsonLoader},
-    loader::{error::LoaderError, trait_::LoaderTrait},
+    json::ConcreteJsonLoader,
+    loader::{
+        error::LoaderError,
+        trait_::{LoaderTrait, DEFAULT_TIMEOUT},
+    },
+    url_helpers::{is_same_origin, parse_and_normalize_url, remove_fragment_from_url},
 };
+use serde::Deserialize;
 use serde_yaml::Value;
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
+use url::Url;
 
+/// Options of [`SerdeYamlLoader`], all disabled by default.
 #[allow(clippy::module_name_repetitions)]
-pub type SerdeYamlLoader = ConcreteJsonLoader<Value>;
+#[derive(Clone, Copy, Debug, Default)]
+pub struct YamlOptions {
+    merge_keys: bool,
+    include_tags: bool,
+    cross_origin_include_tags: bool,
+}
+
+#[allow(clippy::module_name_repetitions)]
+pub type SerdeYamlLoader = ConcreteJsonLoader<Value, YamlOptions>;
+
+impl SerdeYamlLoader {
+    /// Expand [merge keys](https://yaml.org/type/merge.html) (ie. `<<: *defaults`) into the mappings containing them.
+    #[must_use]
+    pub fn with_merge_keys(mut self) -> Self {
+        self.format.merge_keys = true;
+        self
+    }
+
+    /// Replace the values tagged as `!include <url>` with the value identified by the URL (resolved relatively
+    /// to the including document), which is loaded, and cached, via this loader.
+    ///
+    /// Only documents with the same scheme and origin (host and port) of the including document can be included, so a
+    /// remote document cannot include local files (see `with_cross_origin_include_tags`). Invalidating, or expiring,
+    /// an included document invalidates the documents including it.
+    ///
+    /// Included documents are processed before expanding merge keys, so `<<: !include defaults.yaml` is supported.
+    #[must_use]
+    pub fn with_include_tags(mut self) -> Self {
+        self.format.include_tags = true;
+        self
+    }
+
+    /// Replace the values tagged as `!include <url>` as `with_include_tags` does, allowing to include documents of any
+    /// scheme and origin (ie. local files from remote documents, or any absolute URL from documents loaded from bytes).
+    ///
+    /// Enable it only if the loaded documents are trusted, as they could read any file accessible by the process.
+    #[must_use]
+    pub fn with_cross_origin_include_tags(mut self) -> Self {
+        self.format.include_tags = true;
+        self.format.cross_origin_include_tags = true;
+        self
+    }
+
+    /// Parse `content`, retrieved from `url` (if known), applying the configured options.
+    /// `including_urls` holds the documents being loaded, and is used to detect circular inclusions.
+    fn load_yaml(&self, content: &[u8], url: Option<&Url>, timeout: Duration, including_urls: &mut Vec<Url>) -> Result<Value, LoaderError> {
+        let mut value = serde_yaml::from_slice(content).map_err(|serde_error| LoaderError::from(&serde_error))?;
+        if self.format().include_tags {
+            self.resolve_include_tags(&mut value, url, timeout, including_urls)?;
+        }
+        if self.format().merge_keys {
+            value.apply_merge().map_err(|serde_error| LoaderError::from(&serde_error))?;
+        }
+        Ok(value)
+    }
+
+    fn resolve_include_tags(&self, value: &mut Value, url: Option<&Url>, timeout: Duration, including_urls: &mut Vec<Url>) -> Result<(), LoaderError> {
+        match value {
+            Value::Tagged(tagged_value) if tagged_value.tag == "include" => {
+                let included_url = match (&tagged_value.value, url) {
+                    (Value::String(included_url), Some(url)) => url.join(included_url)?,
+                    (Value::String(included_url), None) => Url::parse(included_url)?,
+                    (included_url, _) => return Err(LoaderError::FormatError(format!("!include tag expects an URL, found {:?}", included_url))),
+                };
+                if !self.format().cross_origin_include_tags && !url.map_or(false, |url| is_same_origin(url, &included_url)) {
+                    return Err(LoaderError::FormatError(format!(
+                        "!include of {} from {} is not allowed, as it does not share scheme and origin with the including document",
+                        included_url,
+                        url.map_or("a document without URL", Url::as_str),
+                    )));
+                }
+                *value = self.load_included_value(&included_url, timeout, including_urls)?.as_ref().clone();
+            }
+            Value::Tagged(tagged_value) => self.resolve_include_tags(&mut tagged_value.value, url, timeout, including_urls)?,
+            Value::Sequence(items) => {
+                for item in items {
+                    self.resolve_include_tags(item, url, timeout, including_urls)?;
+                }
+            }
+            Value::Mapping(mapping) => {
+                for item in mapping.values_mut() {
+                    self.resolve_include_tags(item, url, timeout, including_urls)?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn load_included_value(&self, url: &Url, timeout: Duration, including_urls: &mut Vec<Url>) -> Result<Arc<Value>, LoaderError> {
+        let document_url = remove_fragment_from_url(url);
+        if including_urls.contains(&document_url) {
+            return Err(LoaderError::FormatError(format!("Circular !include of {}", document_url)));
+        }
+
+        let document = if let Some(document) = self.get_from_cache(&document_url) {
+            document
+        } else {
+            including_urls.push(document_url.clone());
+            let fetched_content = self.fetch(&document_url, timeout)?;
+            let document = self.load_yaml(&fetched_content.content, Some(&fetched_content.metadata.url), timeout, including_urls)?;
+            let document = Arc::new(self.post_load(&fetched_content.metadata.url, document)?);
+            let _d = including_urls.pop();
+            self.save_fetch_metadata(&document_url, &fetched_content.metadata);
+            self.save_in_cache(&document_url, &document);
+            document
+        };
+        if let Some(including_url) = including_urls.last() {
+            self.add_dependency(including_url, &document_url);
+        }
+
+        match url.fragment() {
+            Some(fragment) => self.extract_fragment(&self.get_final_url(&document_url), fragment, document),
+            None => Ok(document),
+        }
+    }
+}
+
+/// Marker of YAML streams, whose (`---` separated) documents are loaded as a sequence.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct YamlStreamFormat;
+
+/// Loader of multi-document YAML streams (ie. Kubernetes manifests), each document is addressable
+/// via its position within the stream (ie. `https://example.com/manifests.yaml#/0/metadata/name`).
+#[allow(clippy::module_name_repetitions)]
+pub type SerdeYamlStreamLoader = ConcreteJsonLoader<Value, YamlStreamFormat>;
+
+/// Marker of the YAML format, used to load documents via [`SerdeLoader`](crate::traits::loaders::SerdeLoader).
+#[cfg(feature = "serde-loader")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct YamlFormat;
+
+#[cfg(feature = "serde-loader")]
+impl Format for YamlFormat {
+    fn parse(content: &[u8]) -> Result<serde_json::Value, LoaderError> {
+        serde_yaml::from_slice(content).map_err(|serde_error| LoaderError::from(&serde_error))
+    }
+}
+
+#[cfg(feature = "serde-loader")]
+impl Format for YamlStreamFormat {
+    fn parse(content: &[u8]) -> Result<serde_json::Value, LoaderError> {
+        serde_yaml::Deserializer::from_slice(content)
+            .map(serde_json::Value::deserialize)
+            .collect::<Result<_, _>>()
+            .map(serde_json::Value::Array)
+            .map_err(|serde_error| LoaderError::from(&serde_error))
+    }
+}
 
 impl LoaderTrait<Value> for SerdeYamlLoader {
-    fn extract_fragment(&self, fragment: &str, value: Arc<Value>) -> Result<Arc<Value>, LoaderError> {
-        extract_fragment_json_loader(fragment, &value)
+    json_loader_methods!(Value);
+
+    fn load_with_timeout(&self, url: &str, timeout: Duration) -> Result<Arc<Value>, LoaderError> {
+        let url = parse_and_normalize_url(url)?;
+        let document_url = remove_fragment_from_url(&url);
+        let fetched_content = self.fetch(&document_url, timeout)?;
+        let value = self.load_yaml(&fetched_content.content, Some(&fetched_content.metadata.url), timeout, &mut vec![document_url.clone()])?;
+        let value = self.post_load(&fetched_content.metadata.url, value)?;
+        self.save_fetch_metadata(&document_url, &fetched_content.metadata);
+        Ok(Arc::new(value))
     }
 
     fn load_from_bytes(&self, content: &[u8]) -> Result<Value, LoaderError>
     where
         Self: Sized,
     {
-        serde_yaml::from_slice(content).map_err(|serde_error| LoaderError::from(&serde_error))
+        self.load_yaml(content, None, DEFAULT_TIMEOUT, &mut Vec::new())
+    }
+}
+
+impl LoaderTrait<Value> for SerdeYamlStreamLoader {
+    json_loader_methods!(Value);
+
+    fn load_from_bytes(&self, content: &[u8]) -> Result<Value, LoaderError>
+    where
+        Self: Sized,
+    {
+        serde_yaml::Deserializer::from_slice(content)
+            .map(Value::deserialize)
+            .collect::<Result<_, _>>()
+            .map(Value::Sequence)
+            .map_err(|serde_error| LoaderError::from(&serde_error))
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::SerdeYamlLoader;
-    use crate::{loader::error::LoaderError, testing_helpers::MockLoaderRequestBuilder, traits::check_loader};
+    use super::{SerdeYamlLoader, SerdeYamlStreamLoader};
+    use crate::{
+        loader::{
+            error::LoaderError,
+            expiration::ExpirationPolicy,
+            trait_::{GetCache, LoaderTrait},
+            Loader,
+        },
+        testing_helpers::{test_data_file_path, MockLoaderRequestBuilder},
+        traits::check_loader,
+    };
     use serde_yaml::Value;
+    use std::{
+        sync::Arc,
+        time::{Duration, Instant},
+    };
     use test_case::test_case;
+    use url::Url;
 
     macro_rules! yaml {
         ($($json:tt)+) => {{
@@ -72,4 +266,123 @@ mod tests {
             LoaderError::FormatError(value) if "while parsing a node, did not find expected node content at line 2 column 1" == &value
         ));
     }
+
+    #[test]
+    fn test_stream_is_loader() {
+        check_loader::<_, SerdeYamlStreamLoader>()
+    }
+
+    #[test_case("Stream.yaml", "", &yaml![[{"kind": "Namespace", "metadata": {"name": "first"}}, {"kind": "Service", "metadata": {"name": "second"}}]])]
+    #[test_case("Stream.yaml", "/1/metadata/name", &yaml!["second"])]
+    #[test_case("Object.yaml", "", &yaml![[{"key": "Some Text"}]])]
+    #[test_case("Object.yaml", "/0/key", &yaml!["Some Text"])]
+    fn test_load_stream_from_file_valid_content(file_name: &'static str, fragment: &str, expected_loaded_object: &Value) {
+        assert_eq!(
+            &*MockLoaderRequestBuilder::default()
+                .http_path(format!("/#{}", fragment))
+                .resp_body_file_path(vec![file_name])
+                .build()
+                .unwrap()
+                .send_request(&SerdeYamlStreamLoader::default())
+                .unwrap(),
+            expected_loaded_object,
+        );
+    }
+
+    #[test]
+    fn test_load_stream_invalid_content() {
+        assert!(matches!(
+            MockLoaderRequestBuilder::default()
+                .resp_body_file_path(vec!["Invalid.yaml"])
+                .build()
+                .unwrap()
+                .send_request(&SerdeYamlStreamLoader::default())
+                .unwrap_err(),
+            LoaderError::FormatError(_)
+        ));
+    }
+
+    fn test_data_url(file_name: &str) -> Url {
+        Url::from_file_path(test_data_file_path(&[file_name]).unwrap().as_path()).unwrap()
+    }
+
+    #[test_case(&SerdeYamlLoader::default(), &yaml![{"adapter": "sqlite"}] ; "disabled")]
+    #[test_case(&SerdeYamlLoader::default().with_merge_keys(), &yaml![{"adapter": "sqlite", "host": "localhost"}] ; "enabled")]
+    fn test_load_merge_keys(loader: &SerdeYamlLoader, expected_loaded_object: &Value) {
+        let loaded_object = loader.get_or_fetch_with_result(&test_data_url("Merge.yaml")).unwrap();
+        assert_eq!(&loaded_object["development"]["adapter"], &expected_loaded_object["adapter"]);
+        assert_eq!(loaded_object["development"].get("host"), expected_loaded_object.get("host"));
+    }
+
+    #[test]
+    fn test_load_include_tags() {
+        let loader = SerdeYamlLoader::default().with_merge_keys().with_include_tags();
+        let expected_loaded_object: Value = yaml![{
+            "object": {"key": "Some Text"},
+            "key": "Some Text",
+            "merged": {"key": "Some Text", "other": "value"},
+        }];
+        assert_eq!(&*loader.get_or_fetch_with_result(&test_data_url("Include.yaml")).unwrap(), &expected_loaded_object);
+        // Included documents are loaded via the same loader, so they are cached
+        assert!(loader.get_from_cache(&test_data_url("Object.yaml")).is_some());
+    }
+
+    #[test]
+    fn test_load_include_tags_disabled() {
+        let loaded_object = SerdeYamlLoader::default().get_or_fetch_with_result(&test_data_url("Include.yaml")).unwrap();
+        assert!(matches!(&loaded_object["object"], Value::Tagged(_)));
+    }
+
+    #[test_case(&SerdeYamlLoader::default().with_include_tags() => false ; "same_origin_only")]
+    #[test_case(&SerdeYamlLoader::default().with_cross_origin_include_tags() => true ; "cross_origin")]
+    fn test_load_include_tags_of_other_origin(loader: &SerdeYamlLoader) -> bool {
+        let content = format!("object: !include {}", test_data_url("Object.yaml"));
+        match MockLoaderRequestBuilder::default().resp_body(content).build().unwrap().send_request(loader) {
+            Ok(loaded_object) => {
+                assert_eq!(&loaded_object["object"]["key"], &Value::from("Some Text"));
+                true
+            }
+            Err(LoaderError::FormatError(message)) => {
+                assert!(message.starts_with("!include of file://"), "{}", message);
+                false
+            }
+            Err(loader_error) => panic!("Unexpected error {:?}", loader_error),
+        }
+    }
+
+    #[test_case(&SerdeYamlLoader::default().with_include_tags() => false ; "same_origin_only")]
+    #[test_case(&SerdeYamlLoader::default().with_cross_origin_include_tags() => true ; "cross_origin")]
+    fn test_load_from_bytes_include_tags(loader: &SerdeYamlLoader) -> bool {
+        loader.load_from_string(&format!("object: !include {}", test_data_url("Object.yaml"))).is_ok()
+    }
+
+    #[test]
+    fn test_invalidate_included_document() {
+        let loader = SerdeYamlLoader::default().with_include_tags();
+        let loaded_object = loader.get_or_fetch_with_result(&test_data_url("Include.yaml")).unwrap();
+
+        loader.invalidate(&test_data_url("Object.yaml"));
+        assert!(loader.get_from_cache(&test_data_url("Include.yaml")).is_none());
+        assert!(!Arc::ptr_eq(&loaded_object, &loader.get_or_fetch_with_result(&test_data_url("Include.yaml")).unwrap()));
+    }
+
+    #[test]
+    fn test_expire_included_document() {
+        let loader = SerdeYamlLoader::from(Loader::default().with_expiration_policy(ExpirationPolicy::new(Duration::from_secs(10)))).with_include_tags();
+        let loaded_object = loader.get_or_fetch_with_result(&test_data_url("Include.yaml")).unwrap();
+        assert!(Arc::ptr_eq(&loaded_object, &loader.get_or_fetch_with_result(&test_data_url("Include.yaml")).unwrap()));
+
+        let mut metadata = (*loader.get_fetch_metadata_cache().unwrap().get(&test_data_url("Object.yaml")).unwrap()).clone();
+        metadata.fetched_at = Instant::now().checked_sub(Duration::from_secs(20)).unwrap();
+        loader.save_fetch_metadata(&test_data_url("Object.yaml"), &metadata);
+        assert!(!Arc::ptr_eq(&loaded_object, &loader.get_or_fetch_with_result(&test_data_url("Include.yaml")).unwrap()));
+    }
+
+    #[test]
+    fn test_load_circular_include_tags() {
+        assert!(matches!(
+            SerdeYamlLoader::default().with_include_tags().get_or_fetch_with_result(&test_data_url("CircularInclude.yaml")).unwrap_err(),
+            LoaderError::FormatError(message) if message.starts_with("Circular !include of file://")
+        ));
+    }
 }
@@ -0,0 +1,81 @@
+use crate::{json::ConcreteJsonLoader, loader::error::LoaderError, traits::format::Format};
+use serde_json::{Map, Number, Value};
+
+/// [TOML](https://toml.io/) format, whose dates and times are loaded as RFC 3339 strings.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TomlFormat;
+
+#[allow(clippy::module_name_repetitions)]
+pub type TomlLoader = ConcreteJsonLoader<Value, TomlFormat>;
+
+fn toml_to_json(value: toml::Value) -> Result<Value, LoaderError> {
+    Ok(match value {
+        toml::Value::String(string_value) => Value::String(string_value),
+        toml::Value::Integer(integer_value) => Value::Number(Number::from(integer_value)),
+        toml::Value::Float(float_value) => {
+            Value::Number(Number::from_f64(float_value).ok_or_else(|| LoaderError::FormatError(format!("{} cannot be represented as JSON number", float_value)))?)
+        }
+        toml::Value::Boolean(boolean_value) => Value::Bool(boolean_value),
+        // Dates and times have no JSON counterpart, so they are represented with their RFC 3339 string
+        toml::Value::Datetime(datetime_value) => Value::String(datetime_value.to_string()),
+        toml::Value::Array(array_value) => Value::Array(array_value.into_iter().map(toml_to_json).collect::<Result<_, _>>()?),
+        toml::Value::Table(table_value) => Value::Object(
+            table_value
+                .into_iter()
+                .map(|(key, value)| toml_to_json(value).map(|json_value| (key, json_value)))
+                .collect::<Result<Map<_, _>, _>>()?,
+        ),
+    })
+}
+
+impl Format for TomlFormat {
+    fn parse(content: &[u8]) -> Result<Value, LoaderError> {
+        let string_value = std::str::from_utf8(content).map_err(|utf8_error| LoaderError::from(&utf8_error))?;
+        toml::from_str::<toml::Value>(string_value)
+            .map_err(|toml_error| LoaderError::from(&toml_error))
+            .and_then(toml_to_json)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TomlLoader;
+    use crate::{loader::error::LoaderError, testing_helpers::MockLoaderRequestBuilder, traits::check_loader};
+    use serde_json::Value;
+    use test_case::test_case;
+
+    #[test]
+    fn test_is_loader() {
+        check_loader::<_, TomlLoader>()
+    }
+
+    #[test_case("Object.toml", "", &json![{"key": "Some Text"}])]
+    #[test_case("Object.toml", "/key", &json!["Some Text"])]
+    #[test_case("Table.toml", "", &json![{"title": "Table", "owner": {"dob": "1979-05-27T07:32:00-08:00"}, "ports": [8000, 8001], "ratio": 0.5}])]
+    #[test_case("Table.toml", "/ports/1", &json![8001])]
+    fn test_load_from_file_valid_content(file_name: &'static str, fragment: &str, expected_loaded_object: &Value) {
+        assert_eq!(
+            &*MockLoaderRequestBuilder::default()
+                .http_path(format!("/#{}", fragment))
+                .resp_body_file_path(vec![file_name])
+                .build()
+                .unwrap()
+                .send_request(&TomlLoader::default())
+                .unwrap(),
+            expected_loaded_object,
+        );
+    }
+
+    #[test]
+    fn test_load_invalid_content() {
+        assert!(matches!(
+            MockLoaderRequestBuilder::default()
+                .resp_body_file_path(vec!["Invalid.toml"])
+                .build()
+                .unwrap()
+                .send_request(&TomlLoader::default())
+                .unwrap_err(),
+            LoaderError::FormatError(_)
+        ));
+    }
+}
@@ -0,0 +1,26 @@
+use crate::{
+    json::ConcreteJsonLoader,
+    loader::{error::LoaderError, trait_::LoaderTrait},
+};
+use serde_json::Value;
+use std::{fmt::Debug, sync::Arc};
+use url::Url;
+
+/// Format of documents parsed into `serde_json::Value`.
+///
+/// `ConcreteJsonLoader<serde_json::Value, F>` is a loader of the documents encoded according to `F`, while `SerdeLoader`
+/// (see the `serde-loader` feature) resolves the URL fragments before deserializing the identified value into user types.
+pub trait Format: Debug {
+    fn parse(content: &[u8]) -> Result<Value, LoaderError>;
+}
+
+impl<F: Format> LoaderTrait<Value> for ConcreteJsonLoader<Value, F> {
+    json_loader_methods!(Value);
+
+    fn load_from_bytes(&self, content: &[u8]) -> Result<Value, LoaderError>
+    where
+        Self: Sized,
+    {
+        F::parse(content)
+    }
+}
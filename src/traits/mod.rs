@@ -1,15 +1,60 @@
+/// Implement the `LoaderTrait` methods shared by the `ConcreteJsonLoader` based loaders, which cache, resolve
+/// fragments of, post-process and invalidate documents as JSON documents.
+///
+/// `Arc`, `LoaderError` and `Url` are expected to be in scope.
+// Unused if `json-loader` is enabled without any of the loaders
+#[allow(unused_macros)]
+#[cfg(feature = "json-loader")]
+macro_rules! json_loader_methods {
+    ($json_type:ty) => {
+        fn save_in_cache(&self, key: &Url, value: &Arc<$json_type>) {
+            self.save_in_cache_json_loader(key, value)
+        }
+
+        fn extract_fragment(&self, key: &Url, fragment: &str, value: Arc<$json_type>) -> Result<Arc<$json_type>, LoaderError> {
+            self.extract_fragment_json_loader(key, fragment, value)
+        }
+
+        fn post_load(&self, url: &Url, value: $json_type) -> Result<$json_type, LoaderError> {
+            self.post_load_json_loader(url, value)
+        }
+
+        fn invalidate(&self, key: &Url) {
+            self.invalidate_json_loader(key)
+        }
+    };
+}
+
+#[cfg(feature = "trait_cbor")]
+mod _cbor;
+
 #[cfg(feature = "trait_json")]
 mod _json;
 
+#[cfg(feature = "trait_json5")]
+mod _json5;
+
+#[cfg(feature = "trait_msgpack")]
+mod _msgpack;
+
 #[cfg(feature = "trait_serde_json")]
 mod _serde_json;
 
 #[cfg(feature = "trait_serde_yaml")]
 mod _serde_yaml;
 
-#[cfg(all(feature = "json-loader", feature = "testing-helpers"))]
+#[cfg(feature = "trait_toml")]
+mod _toml;
+
+#[cfg(any(feature = "serde-loader", feature = "trait_cbor", feature = "trait_json5", feature = "trait_msgpack", feature = "trait_toml"))]
+mod format;
+
+#[cfg(feature = "trait_rust_type")]
 mod rust_type;
 
+#[cfg(feature = "serde-loader")]
+mod serde_loader;
+
 #[cfg(all(test, feature = "json-loader"))]
 use crate::loader::trait_::LoaderTrait;
 
@@ -17,20 +62,41 @@ use crate::loader::trait_::LoaderTrait;
 use json_trait_rs::JsonType;
 
 pub mod loaders {
+    #[cfg(feature = "trait_cbor")]
+    pub use super::_cbor::{CborFormat, CborLoader};
+
     #[cfg(feature = "trait_json")]
     pub use super::_json::JsonLoader;
 
+    #[cfg(feature = "trait_json5")]
+    pub use super::_json5::{Json5Format, Json5Loader};
+
+    #[cfg(feature = "trait_msgpack")]
+    pub use super::_msgpack::{MessagePackFormat, MessagePackLoader};
+
     #[cfg(feature = "trait_serde_json")]
     pub use super::_serde_json::SerdeJsonLoader;
 
     #[cfg(feature = "trait_serde_yaml")]
-    pub use super::_serde_yaml::SerdeYamlLoader;
+    pub use super::_serde_yaml::{SerdeYamlLoader, SerdeYamlStreamLoader, YamlOptions, YamlStreamFormat};
+
+    #[cfg(feature = "serde-loader")]
+    pub use super::_serde_json::JsonFormat;
+
+    #[cfg(all(feature = "serde-loader", feature = "trait_serde_yaml"))]
+    pub use super::_serde_yaml::YamlFormat;
+
+    #[cfg(any(feature = "serde-loader", feature = "trait_cbor", feature = "trait_json5", feature = "trait_msgpack", feature = "trait_toml"))]
+    pub use super::format::Format;
+
+    #[cfg(feature = "serde-loader")]
+    pub use super::serde_loader::SerdeLoader;
+
+    #[cfg(feature = "trait_toml")]
+    pub use super::_toml::{TomlFormat, TomlLoader};
 
-    // RustTypeLoader is exposed only if testing-helpers feature is enabled
-    // because the Loader depends on serde_json loading capabilities (which for
-    // example are not able to cover the i128 case, but for testing is good enough)
-    #[cfg(all(feature = "json-loader", feature = "testing-helpers"))]
-    pub use super::rust_type::RustTypeLoader;
+    #[cfg(feature = "trait_rust_type")]
+    pub use super::rust_type::{RustTypeLoader, RustTypeOptions};
 }
 
 #[allow(dead_code)]
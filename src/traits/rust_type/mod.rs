@@ -0,0 +1,102 @@
+mod parser;
+
+use crate::{
+    json::ConcreteJsonLoader,
+    loader::{error::LoaderError, trait_::LoaderTrait},
+};
+use json_trait_rs::RustType;
+use std::sync::Arc;
+use url::Url;
+
+/// Options of [`RustTypeLoader`], all disabled by default.
+#[allow(clippy::module_name_repetitions)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RustTypeOptions {
+    strict_numbers: bool,
+}
+
+/// Loader of JSON documents into `RustType`.
+///
+/// Integers are loaded as `RustType::Integer` as long as they fit into `i128`, while the other numbers are loaded as
+/// `RustType::Number` so they are rounded to the closest `f64` (ie. `0.10000000000000000001` is loaded as `0.1`),
+/// unless `with_strict_numbers` is set.
+#[allow(clippy::module_name_repetitions)]
+pub type RustTypeLoader = ConcreteJsonLoader<RustType, RustTypeOptions>;
+
+impl RustTypeLoader {
+    /// Reject, with a `LoaderError::FormatError`, the documents containing numbers that cannot be represented without
+    /// loss of precision (ie. integers beyond `i128` or decimals that `f64` would round).
+    #[must_use]
+    pub fn with_strict_numbers(mut self) -> Self {
+        self.format.strict_numbers = true;
+        self
+    }
+}
+
+impl LoaderTrait<RustType> for RustTypeLoader {
+    json_loader_methods!(RustType);
+
+    fn load_from_bytes(&self, content: &[u8]) -> Result<RustType, LoaderError>
+    where
+        Self: Sized,
+    {
+        parser::parse(content, self.format().strict_numbers).map_err(LoaderError::FormatError)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RustTypeLoader;
+    use crate::{
+        loader::{error::LoaderError, trait_::LoaderTrait},
+        testing_helpers::MockLoaderRequestBuilder,
+        traits::check_loader,
+    };
+    use json_trait_rs::RustType;
+    use test_case::test_case;
+
+    #[test]
+    fn test_is_loader() {
+        check_loader::<_, RustTypeLoader>()
+    }
+
+    #[test_case("Boolean.json", &RustType::from(false))]
+    #[test_case("Integer.json", &RustType::from(1))]
+    #[test_case("LargeInteger.json", &RustType::from(i128::MAX))]
+    #[test_case("Null.json", &RustType::from(()))]
+    #[test_case("String.json", &RustType::from("Some Text"))]
+    fn test_load_valid_content(file_name: &'static str, expected_loaded_object: &RustType) {
+        assert_eq!(
+            &*MockLoaderRequestBuilder::default()
+                .resp_body_file_path(vec![file_name])
+                .build()
+                .unwrap()
+                .send_request(&RustTypeLoader::default())
+                .unwrap(),
+            expected_loaded_object,
+        );
+    }
+
+    #[test]
+    fn test_load_invalid_content() {
+        assert!(matches!(
+            MockLoaderRequestBuilder::default()
+                .resp_body_file_path(vec!["Invalid.json"])
+                .build()
+                .unwrap()
+                .send_request(&RustTypeLoader::default())
+                .unwrap_err(),
+            LoaderError::FormatError(value) if "EOF while parsing an object at line 2 column 0" == &value
+        ));
+    }
+
+    #[test_case(&RustTypeLoader::default() => Ok(RustType::from(0.1)) ; "lossy")]
+    #[test_case(&RustTypeLoader::default().with_strict_numbers() => Err("number cannot be represented without loss of precision at line 1 column 22".to_string()) ; "strict")]
+    fn test_load_imprecise_number(loader: &RustTypeLoader) -> Result<RustType, String> {
+        match loader.load_from_bytes(b"0.10000000000000000001") {
+            Ok(value) => Ok(value),
+            Err(LoaderError::FormatError(message)) => Err(message),
+            Err(loader_error) => panic!("Expected LoaderError::FormatError, received {:?}", loader_error),
+        }
+    }
+}
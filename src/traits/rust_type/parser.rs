@@ -0,0 +1,406 @@
+use json_trait_rs::RustType;
+use std::{collections::HashMap, convert::TryFrom};
+
+// Maximum nesting of lists and objects, it prevents stack overflows while parsing malicious documents
+const RECURSION_LIMIT: usize = 128;
+
+/// Parse the [JSON](https://tools.ietf.org/html/rfc8259) document `content` straight into `RustType`.
+///
+/// Integers are represented as `RustType::Integer` as long as they fit into `i128` (so they are never
+/// rounded), while numbers with fraction or exponent (and larger integers) are represented as `RustType::Number`.
+/// The numbers that `f64` would round are rejected if `strict_numbers` is set, and rounded otherwise.
+pub(in crate) fn parse(content: &[u8], strict_numbers: bool) -> Result<RustType, String> {
+    let content = std::str::from_utf8(content).map_err(|utf8_error| utf8_error.to_string())?;
+    let mut parser = Parser {
+        content,
+        position: 0,
+        depth: 0,
+        strict_numbers,
+    };
+
+    let value = parser.parse_value()?;
+    parser.skip_whitespace();
+    if parser.position < content.len() {
+        Err(parser.error("trailing characters"))
+    } else {
+        Ok(value)
+    }
+}
+
+struct Parser<'content> {
+    content: &'content str,
+    position: usize,
+    depth: usize,
+    strict_numbers: bool,
+}
+
+impl<'content> Parser<'content> {
+    /// Error message locating the current byte (or the end of the document) as done by `serde_json`.
+    fn error(&self, message: &str) -> String {
+        let bytes = self.content.as_bytes();
+        let consumed = &bytes[..(self.position + 1).min(bytes.len())];
+        let mut lines = consumed.split(|byte| *byte == b'\n');
+        let line = lines.clone().count();
+        let column = lines.next_back().map_or(0, <[u8]>::len);
+        format!("{} at line {} column {}", message, line, column)
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.content.as_bytes().get(self.position).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(b' ') | Some(b'\t') | Some(b'\n') | Some(b'\r')) {
+            self.position += 1;
+        }
+    }
+
+    /// Skip the whitespaces and return the following byte, without consuming it.
+    fn next_token(&mut self, eof_message: &str) -> Result<u8, String> {
+        self.skip_whitespace();
+        self.peek().ok_or_else(|| self.error(eof_message))
+    }
+
+    fn parse_value(&mut self) -> Result<RustType, String> {
+        match self.next_token("EOF while parsing a value")? {
+            b'n' => self.parse_literal("null", RustType::Null),
+            b't' => self.parse_literal("true", RustType::Boolean(true)),
+            b'f' => self.parse_literal("false", RustType::Boolean(false)),
+            b'"' => self.parse_string().map(RustType::String),
+            b'-' | b'0'..=b'9' => self.parse_number(),
+            byte @ b'[' | byte @ b'{' => {
+                self.depth += 1;
+                if self.depth > RECURSION_LIMIT {
+                    return Err(self.error("recursion limit exceeded"));
+                }
+                let value = if byte == b'[' { self.parse_list() } else { self.parse_object() };
+                self.depth -= 1;
+                value
+            }
+            _ => Err(self.error("expected value")),
+        }
+    }
+
+    fn parse_literal(&mut self, literal: &str, value: RustType) -> Result<RustType, String> {
+        for expected_byte in literal.bytes() {
+            match self.peek() {
+                Some(byte) if byte == expected_byte => self.position += 1,
+                Some(_) => return Err(self.error("expected ident")),
+                None => return Err(self.error("EOF while parsing a value")),
+            }
+        }
+        Ok(value)
+    }
+
+    fn parse_list(&mut self) -> Result<RustType, String> {
+        const EOF_MESSAGE: &str = "EOF while parsing a list";
+
+        self.position += 1;
+        let mut items = Vec::new();
+        if self.next_token(EOF_MESSAGE)? == b']' {
+            self.position += 1;
+            return Ok(RustType::List(items));
+        }
+
+        loop {
+            items.push(self.parse_value()?);
+            match self.next_token(EOF_MESSAGE)? {
+                b',' => {
+                    self.position += 1;
+                    if self.next_token(EOF_MESSAGE)? == b']' {
+                        return Err(self.error("trailing comma"));
+                    }
+                }
+                b']' => {
+                    self.position += 1;
+                    return Ok(RustType::List(items));
+                }
+                _ => return Err(self.error("expected `,` or `]`")),
+            }
+        }
+    }
+
+    fn parse_object(&mut self) -> Result<RustType, String> {
+        const EOF_MESSAGE: &str = "EOF while parsing an object";
+
+        self.position += 1;
+        let mut object = HashMap::new();
+        if self.next_token(EOF_MESSAGE)? == b'}' {
+            self.position += 1;
+            return Ok(RustType::Object(object));
+        }
+
+        loop {
+            if self.next_token(EOF_MESSAGE)? != b'"' {
+                return Err(self.error("key must be a string"));
+            }
+            let key = self.parse_string()?;
+            if self.next_token(EOF_MESSAGE)? != b':' {
+                return Err(self.error("expected `:`"));
+            }
+            self.position += 1;
+            // As for serde_json, the last occurrence of duplicated keys wins
+            let _d = object.insert(key, self.parse_value()?);
+
+            match self.next_token(EOF_MESSAGE)? {
+                b',' => {
+                    self.position += 1;
+                    if self.next_token(EOF_MESSAGE)? == b'}' {
+                        return Err(self.error("trailing comma"));
+                    }
+                }
+                b'}' => {
+                    self.position += 1;
+                    return Ok(RustType::Object(object));
+                }
+                _ => return Err(self.error("expected `,` or `}`")),
+            }
+        }
+    }
+
+    fn parse_string(&mut self) -> Result<String, String> {
+        self.position += 1;
+        let mut string = String::new();
+        loop {
+            let start = self.position;
+            while matches!(self.peek(), Some(byte) if byte != b'"' && byte != b'\\' && byte >= 0x20) {
+                self.position += 1;
+            }
+            // The loop stops only on ASCII bytes, so the slice boundaries are valid char boundaries
+            string.push_str(&self.content[start..self.position]);
+
+            match self.peek() {
+                Some(b'"') => {
+                    self.position += 1;
+                    return Ok(string);
+                }
+                Some(b'\\') => {
+                    self.position += 1;
+                    string.push(self.parse_escape()?);
+                }
+                Some(_) => return Err(self.error("control character (\\u0000-\\u001F) found while parsing a string")),
+                None => return Err(self.error("EOF while parsing a string")),
+            }
+        }
+    }
+
+    fn parse_escape(&mut self) -> Result<char, String> {
+        let character = match self.peek() {
+            Some(b'"') => '"',
+            Some(b'\\') => '\\',
+            Some(b'/') => '/',
+            Some(b'b') => '\u{8}',
+            Some(b'f') => '\u{c}',
+            Some(b'n') => '\n',
+            Some(b'r') => '\r',
+            Some(b't') => '\t',
+            Some(b'u') => {
+                self.position += 1;
+                return self.parse_unicode_escape();
+            }
+            Some(_) => return Err(self.error("invalid escape")),
+            None => return Err(self.error("EOF while parsing a string")),
+        };
+        self.position += 1;
+        Ok(character)
+    }
+
+    fn parse_unicode_escape(&mut self) -> Result<char, String> {
+        let code_point = match self.parse_hex_digits()? {
+            leading_surrogate @ 0xD800..=0xDBFF => {
+                // Characters outside of the Basic Multilingual Plane are escaped as UTF-16 surrogate pair
+                if !self.content[self.position..].starts_with("\\u") {
+                    return Err(self.error("lone leading surrogate in hex escape"));
+                }
+                self.position += 2;
+                match self.parse_hex_digits()? {
+                    trailing_surrogate @ 0xDC00..=0xDFFF => 0x10000 + ((leading_surrogate - 0xD800) << 10) + (trailing_surrogate - 0xDC00),
+                    _ => return Err(self.error("lone leading surrogate in hex escape")),
+                }
+            }
+            code_point => code_point,
+        };
+        std::char::from_u32(code_point).ok_or_else(|| self.error("invalid unicode code point"))
+    }
+
+    fn parse_hex_digits(&mut self) -> Result<u32, String> {
+        let mut value = 0;
+        for _ in 0..4 {
+            let byte = self.peek().ok_or_else(|| self.error("EOF while parsing a string"))?;
+            let digit = char::from(byte).to_digit(16).ok_or_else(|| self.error("invalid escape"))?;
+            value = value * 16 + digit;
+            self.position += 1;
+        }
+        Ok(value)
+    }
+
+    fn parse_digits(&mut self) -> Result<(), String> {
+        match self.peek() {
+            Some(b'0'..=b'9') => {
+                while matches!(self.peek(), Some(b'0'..=b'9')) {
+                    self.position += 1;
+                }
+                Ok(())
+            }
+            Some(_) => Err(self.error("invalid number")),
+            None => Err(self.error("EOF while parsing a value")),
+        }
+    }
+
+    fn parse_number(&mut self) -> Result<RustType, String> {
+        let start = self.position;
+        if self.peek() == Some(b'-') {
+            self.position += 1;
+        }
+        if self.peek() == Some(b'0') {
+            self.position += 1;
+            if matches!(self.peek(), Some(b'0'..=b'9')) {
+                return Err(self.error("invalid number"));
+            }
+        } else {
+            self.parse_digits()?;
+        }
+
+        let mut is_integer = true;
+        if self.peek() == Some(b'.') {
+            is_integer = false;
+            self.position += 1;
+            self.parse_digits()?;
+        }
+        if matches!(self.peek(), Some(b'e') | Some(b'E')) {
+            is_integer = false;
+            self.position += 1;
+            if matches!(self.peek(), Some(b'+') | Some(b'-')) {
+                self.position += 1;
+            }
+            self.parse_digits()?;
+        }
+
+        let number = &self.content[start..self.position];
+        if is_integer {
+            if let Ok(integer_value) = number.parse() {
+                return Ok(RustType::Integer(integer_value));
+            }
+        }
+        match number.parse::<f64>() {
+            Ok(number_value) if number_value.is_finite() && !self.strict_numbers => Ok(RustType::Number(number_value)),
+            Ok(number_value) if number_value.is_finite() => {
+                // RustType has no arbitrary precision representation, so numbers that f64 would round are rejected
+                let decimal = normalize_decimal(number);
+                if decimal.is_some() && decimal == normalize_decimal(&format!("{:e}", number_value)) {
+                    Ok(RustType::Number(number_value))
+                } else {
+                    Err(self.error("number cannot be represented without loss of precision"))
+                }
+            }
+            _ => Err(self.error("number out of range")),
+        }
+    }
+}
+
+/// Normalize the textual representation of a decimal number into its sign, its significant digits
+/// and the exponent to apply to them, such that equal numbers have equal representations.
+fn normalize_decimal(number: &str) -> Option<(bool, String, i64)> {
+    let (mantissa, exponent) = match number.find(&['e', 'E'][..]) {
+        Some(index) => (&number[..index], number[index + 1..].trim_start_matches('+').parse::<i64>().ok()?),
+        None => (number, 0),
+    };
+    let (is_negative, mantissa) = mantissa.strip_prefix('-').map_or((false, mantissa), |unsigned_mantissa| (true, unsigned_mantissa));
+    let (integer_part, fractional_part) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+
+    let digits = format!("{}{}", integer_part, fractional_part);
+    let significant_digits = digits.trim_start_matches('0').trim_end_matches('0');
+    if significant_digits.is_empty() {
+        return Some((is_negative, String::new(), 0));
+    }
+    let trailing_zeros = i64::try_from(digits.len() - digits.trim_end_matches('0').len()).ok()?;
+    let fractional_digits = i64::try_from(fractional_part.len()).ok()?;
+    Some((
+        is_negative,
+        significant_digits.to_string(),
+        exponent.checked_sub(fractional_digits)?.checked_add(trailing_zeros)?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse;
+    use json_trait_rs::RustType;
+    use std::collections::HashMap;
+    use test_case::test_case;
+
+    #[test_case("null", &RustType::Null)]
+    #[test_case(" true ", &RustType::Boolean(true))]
+    #[test_case("false", &RustType::Boolean(false))]
+    #[test_case("-0", &RustType::Integer(0))]
+    #[test_case("170141183460469231731687303715884105727", &RustType::Integer(i128::MAX))]
+    #[test_case("-170141183460469231731687303715884105728", &RustType::Integer(i128::MIN))]
+    #[test_case("1000000000000000000000000000000000000000", &RustType::Number(1e39))]
+    #[test_case("1.5e3", &RustType::Number(1500.0))]
+    #[test_case("15E+2", &RustType::Number(1500.0))]
+    #[test_case("-0.25", &RustType::Number(-0.25))]
+    #[test_case("0.1", &RustType::Number(0.1))]
+    #[test_case("-0.0e5", &RustType::Number(-0.0))]
+    #[test_case(r#""a\"b\\c\/d\b\f\n\r\t""#, &RustType::String("a\"b\\c/d\u{8}\u{c}\n\r\t".to_string()))]
+    #[test_case(r#""\u00e8 \ud83d\ude00 è""#, &RustType::String("è 😀 è".to_string()))]
+    #[test_case("[]", &RustType::List(vec![]))]
+    #[test_case("[1, [2.5, \"3\"]]", &RustType::List(vec![RustType::Integer(1), RustType::List(vec![RustType::Number(2.5), RustType::String("3".to_string())])]))]
+    #[test_case("{}", &RustType::Object(HashMap::new()))]
+    #[test_case("{\"key\": {\"key\": null}, \"other\": true, \"key\": 1}", &RustType::Object(vec![("key".to_string(), RustType::Integer(1)), ("other".to_string(), RustType::Boolean(true))].into_iter().collect()))]
+    fn test_parse_valid_content(content: &str, expected_value: &RustType) {
+        assert_eq!(&parse(content.as_bytes(), true).unwrap(), expected_value);
+        assert_eq!(&parse(content.as_bytes(), false).unwrap(), expected_value);
+    }
+
+    #[test_case("170141183460469231731687303715884105728", &RustType::Number(1.701_411_834_604_692_3e38))]
+    #[test_case("0.10000000000000000001", &RustType::Number(0.1))]
+    #[test_case("1e-400", &RustType::Number(0.0))]
+    fn test_parse_lossy_number(content: &str, expected_value: &RustType) {
+        assert_eq!(&parse(content.as_bytes(), false).unwrap(), expected_value);
+    }
+
+    #[test_case("", "EOF while parsing a value at line 1 column 0")]
+    #[test_case("{\n", "EOF while parsing an object at line 2 column 0")]
+    #[test_case("[1", "EOF while parsing a list at line 1 column 2")]
+    #[test_case("\"text", "EOF while parsing a string at line 1 column 5")]
+    #[test_case("nul", "EOF while parsing a value at line 1 column 3")]
+    #[test_case("nil", "expected ident at line 1 column 2")]
+    #[test_case("[1 2]", "expected `,` or `]` at line 1 column 4")]
+    #[test_case("[1,]", "trailing comma at line 1 column 4")]
+    #[test_case("{\"key\": 1,}", "trailing comma at line 1 column 11")]
+    #[test_case("{\"key\" 1}", "expected `:` at line 1 column 8")]
+    #[test_case("{1: 1}", "key must be a string at line 1 column 2")]
+    #[test_case("{\"key\": 1 \"other\": 2}", "expected `,` or `}` at line 1 column 11")]
+    #[test_case("01", "invalid number at line 1 column 2")]
+    #[test_case("1.", "EOF while parsing a value at line 1 column 2")]
+    #[test_case("-a", "invalid number at line 1 column 2")]
+    #[test_case("1e400", "number out of range at line 1 column 5")]
+    #[test_case("\"\\x\"", "invalid escape at line 1 column 3")]
+    #[test_case("\"\\ud83d\"", "lone leading surrogate in hex escape at line 1 column 8")]
+    #[test_case("\"\\udc00\"", "invalid unicode code point at line 1 column 8")]
+    #[test_case("\"\t\"", "control character (\\u0000-\\u001F) found while parsing a string at line 1 column 2")]
+    #[test_case("1 2", "trailing characters at line 1 column 3")]
+    #[test_case("'text'", "expected value at line 1 column 1")]
+    fn test_parse_invalid_content(content: &str, expected_message: &str) {
+        assert_eq!(parse(content.as_bytes(), false).unwrap_err(), expected_message);
+    }
+
+    #[test_case("170141183460469231731687303715884105728", "number cannot be represented without loss of precision at line 1 column 39")]
+    #[test_case("0.10000000000000000001", "number cannot be represented without loss of precision at line 1 column 22")]
+    #[test_case("1e-400", "number cannot be represented without loss of precision at line 1 column 6")]
+    #[test_case("1e400", "number out of range at line 1 column 5")]
+    fn test_parse_strict_number(content: &str, expected_message: &str) {
+        assert_eq!(parse(content.as_bytes(), true).unwrap_err(), expected_message);
+    }
+
+    #[test]
+    fn test_parse_recursion_limit() {
+        assert_eq!(parse("[".repeat(129).as_bytes(), false).unwrap_err(), "recursion limit exceeded at line 1 column 129");
+        assert!(parse(format!("{}{}", "[".repeat(128), "]".repeat(128)).as_bytes(), false).is_ok());
+    }
+
+    #[test]
+    fn test_parse_invalid_utf8() {
+        assert!(parse(b"\"\xff\"", false).is_err());
+    }
+}
@@ -0,0 +1,354 @@
+#[cfg(feature = "file-watcher")]
+use crate::loader::watcher::FileWatcher;
+use crate::{
+    json::ConcreteJsonLoader,
+    loader::{
+        archive::Archive,
+        credentials::CredentialsProvider,
+        dependencies::DocumentDependencies,
+        error::LoaderError,
+        expiration::ExpirationPolicy,
+        metadata::FetchMetadata,
+        refresh::Refresh,
+        timeout::Timeouts,
+        trait_::{GetCache, GetClient, LoaderTrait},
+        Loader,
+    },
+    thread_safe_cache::{ThreadSafeCacheImpl, ThreadSafeCacheTrait},
+    traits::format::Format,
+    url_helpers::{parse_and_normalize_url, remove_fragment_from_url},
+};
+use reqwest::blocking::Client;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::{
+    fmt::{Debug, Error, Formatter},
+    sync::Arc,
+};
+use url::Url;
+
+/// Loader deserializing documents, encoded according to `F`, directly into `T`.
+///
+/// The fragment of the URL (JSON pointer or plain-name fragment) selects the value to deserialize,
+/// so `https://example.com/config.yaml#/database` could be loaded as a `DatabaseConfig`.
+/// Parsed documents and deserialized values are cached independently, so different fragments
+/// of the same document are fetched only once.
+///
+/// Documents are loaded via the underlying [`ConcreteJsonLoader`], so its post-processing (ie. environment variables
+/// interpolation) and the hooks of its [`Loader`] are applied before deserialization.
+pub struct SerdeLoader<T, F> {
+    documents: ConcreteJsonLoader<Value, F>,
+    cache: ThreadSafeCacheImpl<Url, T>,
+    // Documents the cached values were deserialized from, such that values of reloaded documents are deserialized again
+    sources: ThreadSafeCacheImpl<Url, Value>,
+    // Keys of the cached values of fragments, as dependents of their (fragmentless) document URL, such that invalidations
+    // remove the values of the invalidated document only
+    fragment_keys: DocumentDependencies,
+}
+
+impl<T, F: Format> Debug for SerdeLoader<T, F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        f.debug_struct("SerdeLoader")
+            .field("documents", &self.documents)
+            .field("cache", &self.cache)
+            .field("sources", &self.sources)
+            .field("fragment_keys", &self.fragment_keys)
+            .finish()
+    }
+}
+
+impl<T, F: Default> Default for SerdeLoader<T, F> {
+    fn default() -> Self {
+        Self::from(Loader::default())
+    }
+}
+
+impl<T, F: Default> From<Loader<Value>> for SerdeLoader<T, F> {
+    fn from(loader: Loader<Value>) -> Self {
+        Self::from(ConcreteJsonLoader::from(loader))
+    }
+}
+
+impl<T, F> From<ConcreteJsonLoader<Value, F>> for SerdeLoader<T, F> {
+    fn from(documents: ConcreteJsonLoader<Value, F>) -> Self {
+        Self {
+            documents,
+            cache: ThreadSafeCacheImpl::default(),
+            sources: ThreadSafeCacheImpl::default(),
+            fragment_keys: DocumentDependencies::default(),
+        }
+    }
+}
+
+impl<T, F> GetCache<T> for SerdeLoader<T, F> {
+    fn get_cache(&self) -> &dyn ThreadSafeCacheTrait<Url, T> {
+        &self.cache
+    }
+
+    fn get_fetch_metadata_cache(&self) -> Option<&dyn ThreadSafeCacheTrait<Url, FetchMetadata>> {
+        GetCache::<Value>::get_fetch_metadata_cache(&self.documents)
+    }
+
+    fn get_archives_cache(&self) -> Option<&dyn ThreadSafeCacheTrait<Url, Archive>> {
+        GetCache::<Value>::get_archives_cache(&self.documents)
+    }
+
+    fn get_expiration_policy(&self) -> Option<&ExpirationPolicy> {
+        GetCache::<Value>::get_expiration_policy(&self.documents)
+    }
+
+    fn get_refreshes_cache(&self) -> Option<Arc<dyn ThreadSafeCacheTrait<Url, Refresh> + Send + Sync>> {
+        GetCache::<Value>::get_refreshes_cache(&self.documents)
+    }
+
+    #[cfg(feature = "file-watcher")]
+    fn get_file_watcher(&self) -> Option<&FileWatcher> {
+        GetCache::<Value>::get_file_watcher(&self.documents)
+    }
+
+    fn get_dependencies(&self) -> Option<&DocumentDependencies> {
+        GetCache::<Value>::get_dependencies(&self.documents)
+    }
+}
+
+impl<T, F> GetClient<T> for SerdeLoader<T, F> {
+    fn get_client(&self) -> &Client {
+        GetClient::<Value>::get_client(&self.documents)
+    }
+
+    fn get_credentials_provider(&self) -> Option<&CredentialsProvider> {
+        GetClient::<Value>::get_credentials_provider(&self.documents)
+    }
+
+    fn get_timeouts(&self, url: &Url) -> Timeouts {
+        GetClient::<Value>::get_timeouts(&self.documents, url)
+    }
+
+    fn get_client_with_timeouts(&self, timeouts: &Timeouts) -> Result<Client, LoaderError> {
+        GetClient::<Value>::get_client_with_timeouts(&self.documents, timeouts)
+    }
+
+    fn get_max_content_size(&self) -> usize {
+        GetClient::<Value>::get_max_content_size(&self.documents)
+    }
+}
+
+impl<T: DeserializeOwned, F: Format> SerdeLoader<T, F> {
+    /// Retrieve the (not yet deserialized) value identified by `key`.
+    pub fn get_or_fetch_document_with_result(&self, key: &Url) -> Result<Arc<Value>, LoaderError> {
+        self.documents.get_or_fetch_with_result(&parse_and_normalize_url(key.as_str())?)
+    }
+}
+
+impl<T: DeserializeOwned, F: Format> LoaderTrait<T> for SerdeLoader<T, F> {
+    fn load_from_bytes(&self, content: &[u8]) -> Result<T, LoaderError> {
+        serde_json::from_value(F::parse(content)?).map_err(|serde_error| LoaderError::from(&serde_error))
+    }
+
+    fn invalidate(&self, key: &Url) {
+        // Values of the documents built on top of the invalidated one are deserialized again once their sources change
+        let fragmentless_url = remove_fragment_from_url(key);
+        let fragment_keys = self.fragment_keys.remove(&fragmentless_url);
+        for value_key in [key.clone(), fragmentless_url].iter().chain(&fragment_keys) {
+            let _d = self.cache.remove(value_key);
+            let _d = self.sources.remove(value_key);
+        }
+        self.documents.invalidate(key);
+    }
+
+    fn get_or_fetch_with_cache_status(&self, key: &Url) -> Result<(Arc<T>, bool), LoaderError> {
+        let key = parse_and_normalize_url(key.as_str())?;
+        // Values are deserialized again only from reloaded documents, so the documents tell whether the value was fetched
+        let (source, from_cache) = self.documents.get_or_fetch_with_cache_status(&remove_fragment_from_url(&key))?;
+        if let (Some(value), Some(value_source)) = (self.get_from_cache(&key), self.sources.get(&key)) {
+            if Arc::ptr_eq(&source, &value_source) {
+                return Ok((value, from_cache));
+            }
+        }
+
+        let document = self.documents.get_or_fetch_with_result(&key)?;
+        let value = Arc::new(T::deserialize(&*document).map_err(|serde_error| LoaderError::from(&serde_error))?);
+        self.save_in_cache(&key, &value);
+        self.sources.set(&key, source);
+        if key.fragment().is_some() {
+            self.fragment_keys.add(&key, &remove_fragment_from_url(&key));
+        }
+        Ok((value, from_cache))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SerdeLoader;
+    #[cfg(feature = "trait_serde_yaml")]
+    use crate::traits::loaders::{YamlFormat, YamlStreamFormat};
+    use crate::{
+        json::{ConcreteJsonLoader, EnvInterpolator},
+        loader::{error::LoaderError, trait_::LoaderTrait, Loader},
+        testing_helpers::{test_data_file_path, MockLoaderRequestBuilder},
+        traits::loaders::JsonFormat,
+    };
+    use serde::Deserialize;
+    use serde_json::Value;
+    use std::sync::Arc;
+    use test_case::test_case;
+    use url::Url;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Object {
+        key: String,
+    }
+
+    #[test]
+    fn test_load_struct() {
+        assert_eq!(
+            &*MockLoaderRequestBuilder::default()
+                .resp_body_file_path(vec!["Object.json"])
+                .build()
+                .unwrap()
+                .send_request(&SerdeLoader::<Object, JsonFormat>::default())
+                .unwrap(),
+            &Object { key: "Some Text".to_string() },
+        );
+    }
+
+    #[cfg(feature = "trait_serde_yaml")]
+    #[test]
+    fn test_load_struct_from_yaml() {
+        assert_eq!(
+            &*MockLoaderRequestBuilder::default()
+                .resp_body_file_path(vec!["Object.yaml"])
+                .build()
+                .unwrap()
+                .send_request(&SerdeLoader::<Object, YamlFormat>::default())
+                .unwrap(),
+            &Object { key: "Some Text".to_string() },
+        );
+    }
+
+    #[cfg(feature = "trait_serde_yaml")]
+    #[test]
+    fn test_load_structs_from_yaml_stream() {
+        assert_eq!(
+            &*MockLoaderRequestBuilder::default()
+                .resp_body_file_path(vec!["Object.yaml"])
+                .build()
+                .unwrap()
+                .send_request(&SerdeLoader::<Vec<Object>, YamlStreamFormat>::default())
+                .unwrap(),
+            &vec![Object { key: "Some Text".to_string() }],
+        );
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Schema {
+        #[serde(rename = "type")]
+        type_: String,
+    }
+
+    #[test_case("/$defs/anchored" => "string" ; "json_pointer")]
+    #[test_case("bar" => "integer" ; "anchor")]
+    fn test_fragment_is_selected_before_deserialization(fragment: &str) -> String {
+        MockLoaderRequestBuilder::default()
+            .http_path(format!("/#{}", fragment))
+            .resp_body_file_path(vec!["Schema.json"])
+            .build()
+            .unwrap()
+            .send_request(&SerdeLoader::<Schema, JsonFormat>::from(ConcreteJsonLoader::default().with_schema_resources()))
+            .unwrap()
+            .type_
+            .clone()
+    }
+
+    #[test]
+    fn test_load_cached_value() {
+        let loader = SerdeLoader::<Object, JsonFormat>::default();
+        MockLoaderRequestBuilder::default()
+            .resp_body_file_path(vec!["Object.json"])
+            .build()
+            .unwrap()
+            .run_in_mock_context(&|url| {
+                let value = loader.get_or_fetch_with_result(url).unwrap();
+                assert!(Arc::ptr_eq(&value, &loader.get_or_fetch_with_result(url).unwrap()));
+                // The parsed document is cached too, so other fragments are resolved without fetching it again
+                assert!(loader.get_or_fetch_document_with_result(&url.join("#/key").unwrap()).is_ok());
+            });
+    }
+
+    #[test]
+    fn test_load_struct_with_env_interpolation() {
+        std::env::set_var("LOADER_RS_SERDE_LOADER_KEY", "value");
+        assert_eq!(
+            &*MockLoaderRequestBuilder::default()
+                .resp_body(r#"{"key": "${LOADER_RS_SERDE_LOADER_KEY}"}"#)
+                .build()
+                .unwrap()
+                .send_request(&SerdeLoader::<Object, JsonFormat>::from(
+                    ConcreteJsonLoader::default().with_env_interpolation(EnvInterpolator::default())
+                ))
+                .unwrap(),
+            &Object { key: "value".to_string() },
+        );
+    }
+
+    #[test]
+    fn test_load_struct_with_hook() {
+        let loader = SerdeLoader::<Object, JsonFormat>::from(Loader::default().with_hook(|_, mut value: Value| {
+            value["key"] = Value::from("hooked");
+            Ok(value)
+        }));
+        assert_eq!(
+            &*MockLoaderRequestBuilder::default()
+                .resp_body_file_path(vec!["Object.json"])
+                .build()
+                .unwrap()
+                .send_request(&loader)
+                .unwrap(),
+            &Object { key: "hooked".to_string() },
+        );
+    }
+
+    #[test]
+    fn test_load_reloaded_document() {
+        let loader = SerdeLoader::<Object, JsonFormat>::default();
+        MockLoaderRequestBuilder::default()
+            .resp_body_file_path(vec!["Object.json"])
+            .expected_mock_calls(2_usize)
+            .build()
+            .unwrap()
+            .run_in_mock_context(&|url| {
+                let value = loader.get_or_fetch_with_result(url).unwrap();
+                // The document is reloaded without invalidating the loader, so the value is deserialized again
+                LoaderTrait::<Value>::invalidate(&loader.documents, url);
+                assert!(!Arc::ptr_eq(&value, &loader.get_or_fetch_with_result(url).unwrap()));
+            });
+    }
+
+    #[test]
+    fn test_invalidate() {
+        let url = |file_name: &str| Url::from_file_path(test_data_file_path(&[file_name]).unwrap()).unwrap();
+        let loader = SerdeLoader::<Value, JsonFormat>::default();
+        let object_key_url = url("Object.json").join("#/key").unwrap();
+        let object_key = loader.get_or_fetch_with_result(&object_key_url).unwrap();
+        let integer = loader.get_or_fetch_with_result(&url("Integer.json")).unwrap();
+
+        loader.invalidate(&url("Object.json"));
+        assert!(loader.get_from_cache(&object_key_url).is_none());
+        assert!(!Arc::ptr_eq(&object_key, &loader.get_or_fetch_with_result(&object_key_url).unwrap()));
+        // Values of the other documents are kept
+        assert!(Arc::ptr_eq(&integer, &loader.get_from_cache(&url("Integer.json")).unwrap()));
+    }
+
+    #[test]
+    fn test_load_not_matching_type() {
+        assert!(matches!(
+            MockLoaderRequestBuilder::default()
+                .resp_body_file_path(vec!["String.json"])
+                .build()
+                .unwrap()
+                .send_request(&SerdeLoader::<Object, JsonFormat>::default())
+                .unwrap_err(),
+            LoaderError::FormatError(_)
+        ));
+    }
+}
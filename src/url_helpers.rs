@@ -1,3 +1,5 @@
+#[cfg(feature = "json-loader")]
+use percent_encoding::percent_decode_str;
 #[cfg(feature = "regular_expression")]
 use regex::Regex;
 use std::cell::RefCell;
@@ -8,6 +10,9 @@ pub enum UrlError {
     ParseError(ParseError),
     SyntaxViolation(SyntaxViolation),
     JsonFragmentError(String),
+    InvalidJsonPointer(String),
+    InvalidJsonPath(String),
+    ArchiveEntryError(String),
 }
 
 impl From<ParseError> for UrlError {
@@ -24,6 +29,13 @@ impl From<SyntaxViolation> for UrlError {
     }
 }
 
+/// Whether `url` and `other_url` share scheme, host and port.
+///
+/// Differently from comparing `Url::origin`, `file://` URLs (whose origins are opaque) share the same origin.
+pub(in crate) fn is_same_origin(url: &Url, other_url: &Url) -> bool {
+    url.scheme() == other_url.scheme() && url.host() == other_url.host() && url.port_or_known_default() == other_url.port_or_known_default()
+}
+
 pub(in crate) fn parse_and_normalize_url(url: &str) -> Result<Url, UrlError> {
     let mut maybe_syntax_violation: RefCell<Option<SyntaxViolation>> = RefCell::new(None);
     let mut url = Url::options()
@@ -35,15 +47,11 @@ pub(in crate) fn parse_and_normalize_url(url: &str) -> Result<Url, UrlError> {
         return Err(UrlError::from(*violation));
     }
 
-    let fragments = url.fragment().unwrap_or("").trim_end_matches('/');
-    let owned_fragments = if fragments.is_empty() {
-        "/".to_string()
-    } else if fragments.starts_with('/') {
-        fragments.to_string()
-    } else {
-        format!("/{}", fragments)
-    };
-    url.set_fragment(Some(owned_fragments.as_str()));
+    // Fragments are not rewritten as trailing slashes are meaningful in JSON pointers (`/a/` addresses
+    // the empty key of `/a`) and fragments not starting with `/` are plain-name fragments (ie. `#foo`)
+    if url.fragment() == Some("") {
+        url.set_fragment(None);
+    }
 
     if url.path().is_empty() {
         url.set_path("/");
@@ -58,20 +66,87 @@ pub(in crate) fn remove_fragment_from_url(url: &Url) -> Url {
     fragment_less_key
 }
 
+/// Convert an URL fragment into the reference tokens of the [RFC 6901](https://tools.ietf.org/html/rfc6901) JSON pointer it represents.
+///
+/// The fragment is percent-decoded first (as defined in [Section 6](https://tools.ietf.org/html/rfc6901#section-6))
+/// and then each reference token is unescaped (`~1` into `/` and `~0` into `~`).
+#[cfg(feature = "json-loader")]
+pub(in crate) fn json_pointer_reference_tokens(fragment: &str) -> Result<Vec<String>, UrlError> {
+    let json_pointer = percent_decode_str(fragment)
+        .decode_utf8()
+        .map_err(|utf8_error| UrlError::InvalidJsonPointer(format!("Fragment '{}' is not a valid UTF-8 string: {}", fragment, utf8_error)))?;
+
+    if json_pointer.is_empty() {
+        return Ok(Vec::new());
+    } else if !json_pointer.starts_with('/') {
+        return Err(UrlError::InvalidJsonPointer(format!("JSON pointer '{}' does not start with '/'", json_pointer)));
+    }
+
+    json_pointer
+        .split('/')
+        .skip(1)
+        .enumerate()
+        .map(|(position, escaped_token)| {
+            let mut token = String::with_capacity(escaped_token.len());
+            let mut chars = escaped_token.chars();
+            while let Some(chr) = chars.next() {
+                if chr == '~' {
+                    match chars.next() {
+                        Some('0') => token.push('~'),
+                        Some('1') => token.push('/'),
+                        _ => {
+                            return Err(UrlError::InvalidJsonPointer(format!(
+                                "Invalid escape sequence in token '{}' (position {}) of JSON pointer '{}'",
+                                escaped_token, position, json_pointer
+                            )))
+                        }
+                    }
+                } else {
+                    token.push(chr);
+                }
+            }
+            Ok(token)
+        })
+        .collect()
+}
+
+/// Build the JSON pointer (not percent-encoded) that refers to the given reference tokens.
+#[cfg(feature = "json-loader")]
+pub(in crate) fn json_pointer_from_reference_tokens<S: AsRef<str>>(reference_tokens: &[S]) -> String {
+    let mut json_pointer = String::new();
+    for reference_token in reference_tokens {
+        json_pointer.push('/');
+        json_pointer.push_str(&reference_token.as_ref().replace('~', "~0").replace('/', "~1"));
+    }
+    json_pointer
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{parse_and_normalize_url, remove_fragment_from_url, UrlError};
+    use super::{is_same_origin, parse_and_normalize_url, remove_fragment_from_url, UrlError};
+    #[cfg(feature = "json-loader")]
+    use super::{json_pointer_from_reference_tokens, json_pointer_reference_tokens};
     use test_case::test_case;
     use url::{ParseError, SyntaxViolation, Url};
 
-    #[test_case("memory://", "memory:///#/" ; "url_with_no_path_no_fragment")]
-    #[test_case("memory://#", "memory:///#/" ; "url_with_no_path")]
-    #[test_case("memory:///", "memory:///#/" ; "url_with_no_fragment")]
-    #[test_case("memory:///#", "memory:///#/" ; "url_with_path_and_fragment")]
+    #[test_case("http://example.com/a.yaml", "http://example.com:80/b/c.yaml" => true ; "same_origin")]
+    #[test_case("file:///a.yaml", "file:///b/c.yaml" => true ; "local_files")]
+    #[test_case("http://example.com/a.yaml", "https://example.com/a.yaml" => false ; "different_scheme")]
+    #[test_case("http://example.com/a.yaml", "http://example.org/a.yaml" => false ; "different_host")]
+    #[test_case("http://example.com/a.yaml", "http://example.com:8080/a.yaml" => false ; "different_port")]
+    #[test_case("http://example.com/a.yaml", "file:///etc/passwd" => false ; "remote_and_local")]
+    fn test_is_same_origin(url: &str, other_url: &str) -> bool {
+        is_same_origin(&Url::parse(url).unwrap(), &Url::parse(other_url).unwrap())
+    }
+
+    #[test_case("memory://", "memory:///" ; "url_with_no_path_no_fragment")]
+    #[test_case("memory://#", "memory:///" ; "url_with_no_path")]
+    #[test_case("memory:///", "memory:///" ; "url_with_no_fragment")]
+    #[test_case("memory:///#", "memory:///" ; "url_with_path_and_fragment")]
     #[test_case("memory:///#/", "memory:///#/" ; "url_with_path_and_fragment_normalized")]
-    #[test_case("memory:///#fragment", "memory:///#/fragment" ; "url_with_path_and_not_empty_fragment_1")]
+    #[test_case("memory:///#fragment", "memory:///#fragment" ; "url_with_path_and_plain_name_fragment")]
     #[test_case("memory:///#/fragment", "memory:///#/fragment" ; "url_with_path_and_not_empty_fragment_2")]
-    #[test_case("memory:///#/fragment/", "memory:///#/fragment" ; "url_with_path_and_not_empty_fragment_3")]
+    #[test_case("memory:///#/fragment/", "memory:///#/fragment/" ; "url_with_path_and_not_empty_fragment_3")]
     fn test_parse_and_normalize_url_valid_case(url_str: &str, expected_result_str: &str) {
         assert_eq!(parse_and_normalize_url(url_str).unwrap().as_str(), expected_result_str);
     }
@@ -90,4 +165,27 @@ mod tests {
     fn test_remove_fra(url_str: &str) -> String {
         remove_fragment_from_url(&Url::parse(url_str).unwrap()).to_string()
     }
+
+    #[cfg(feature = "json-loader")]
+    #[test_case("" => Ok(vec![]) ; "whole_document")]
+    #[test_case("/" => Ok(vec!["".to_string()]) ; "empty_key")]
+    #[test_case("/a/" => Ok(vec!["a".to_string(), "".to_string()]) ; "trailing_empty_key")]
+    #[test_case("/a~1b/m~0n" => Ok(vec!["a/b".to_string(), "m~n".to_string()]) ; "escaped_tokens")]
+    #[test_case("/~01" => Ok(vec!["~1".to_string()]) ; "escapes_are_not_applied_twice")]
+    #[test_case("/a%20b/c%25d" => Ok(vec!["a b".to_string(), "c%d".to_string()]) ; "percent_encoded_tokens")]
+    #[test_case("a" => Err(UrlError::InvalidJsonPointer("JSON pointer 'a' does not start with '/'".to_string())) ; "relative_pointer")]
+    #[test_case("/a/b~2" => Err(UrlError::InvalidJsonPointer("Invalid escape sequence in token 'b~2' (position 1) of JSON pointer '/a/b~2'".to_string())) ; "invalid_escape")]
+    #[test_case("/%FF" => Err(UrlError::InvalidJsonPointer("Fragment '/%FF' is not a valid UTF-8 string: invalid utf-8 sequence of 1 bytes from index 1".to_string())) ; "invalid_utf8")]
+    fn test_json_pointer_reference_tokens(fragment: &str) -> Result<Vec<String>, UrlError> {
+        json_pointer_reference_tokens(fragment)
+    }
+
+    #[cfg(feature = "json-loader")]
+    #[test_case(&[] => "" ; "whole_document")]
+    #[test_case(&["key"] => "/key" ; "single_token")]
+    #[test_case(&["a", ""] => "/a/" ; "trailing_empty_token")]
+    #[test_case(&["a/b", "m~n", "~/"] => "/a~1b/m~0n/~0~1" ; "escaped_tokens")]
+    fn test_json_pointer_from_reference_tokens(reference_tokens: &[&str]) -> String {
+        json_pointer_from_reference_tokens(reference_tokens)
+    }
 }